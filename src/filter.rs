@@ -0,0 +1,73 @@
+#[derive(Debug)]
+pub struct FilterState {
+    pub choices: Vec<String>,
+    pub query: String,
+    /// Indices of the choices matching `query`, best match first
+    pub matches: Vec<usize>,
+    /// Index into `matches`
+    pub cursor_loc: usize,
+    /// First visible row
+    pub offset: usize,
+}
+
+impl FilterState {
+    pub fn new(choices: Vec<String>) -> Self {
+        FilterState {
+            matches: (0..choices.len()).collect(),
+            choices,
+            query: String::new(),
+            cursor_loc: 0,
+            offset: 0,
+        }
+    }
+
+    /// Index of the choice under the cursor
+    pub fn current(&self) -> Option<usize> {
+        self.matches.get(self.cursor_loc).copied()
+    }
+
+    /// Rank the choices against the query
+    pub fn refilter(&mut self) {
+        let mut scored: Vec<(usize, usize)> = (0..self.choices.len())
+            .filter_map(|i| fuzzy_match(&self.query, &self.choices[i]).map(|(score, _)| (score, i)))
+            .collect();
+        scored.sort();
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.cursor_loc = 0;
+        self.offset = 0;
+    }
+
+    /// Keep the cursor within a viewport of `height` rows
+    pub fn scroll(&mut self, height: usize) {
+        if self.cursor_loc < self.offset {
+            self.offset = self.cursor_loc;
+        } else if self.cursor_loc >= self.offset + height {
+            self.offset = self.cursor_loc + 1 - height;
+        }
+    }
+}
+
+/// Case-insensitive subsequence match of `query` in `candidate`.
+/// Returns a score where lower is better and the char indices of the matched
+/// characters, or `None` if it does not match.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(usize, Vec<usize>)> {
+    let mut chars = candidate
+        .chars()
+        .enumerate()
+        .flat_map(|(i, c)| c.to_lowercase().map(move |c| (i, c)));
+    let mut positions: Vec<usize> = vec![];
+    for q in query.chars().flat_map(char::to_lowercase) {
+        let (i, _) = chars.find(|(_, c)| *c == q)?;
+        // a character may lowercase to several
+        if positions.last() != Some(&i) {
+            positions.push(i);
+        }
+    }
+
+    // prefer tight matches that start early
+    let score = match (positions.first(), positions.last()) {
+        (Some(first), Some(last)) => (last - first) * 2 + first,
+        _ => 0,
+    };
+    Some((score, positions))
+}
@@ -1,32 +1,67 @@
+mod filter;
+mod menu;
+mod pager;
+mod pause;
+mod progress;
+mod slider;
+mod stopwatch;
+mod textarea;
+mod timer;
+
 use std::{
-    io::{stderr, stdin, Stderr},
+    collections::VecDeque,
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, stderr, stdin, BufRead, BufReader, BufWriter, IsTerminal, Read, Stderr, Write},
     num::NonZeroUsize,
+    os::fd::{BorrowedFd, RawFd},
+    panic,
+    path::PathBuf,
     process::{Child, Command, Stdio},
+    sync::mpsc::{channel, Receiver, TryRecvError},
+    thread,
     time::{Duration, Instant},
 };
 
 use crossterm::{
-    cursor::{Hide, MoveTo, Show},
-    event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers},
-    execute,
+    cursor::{Hide, MoveTo, MoveUp, SetCursorStyle, Show},
+    event::{
+        poll, read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    execute, queue,
     style::{
         Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
     },
     terminal::{
-        disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+        disable_raw_mode, enable_raw_mode, size, Clear, ClearType, EnterAlternateScreen,
         LeaveAlternateScreen,
     },
 };
+use filter::{fuzzy_match, FilterState};
 use lru::LruCache;
+use menu::MenuState;
+use pager::PagerState;
+use pause::PauseState;
+use progress::ProgressState;
+use regex::Regex;
+use slider::SliderState;
+use stopwatch::StopwatchState;
 use structopt::{clap::arg_enum, StructOpt};
-use unicode_segmentation::{Graphemes, UnicodeSegmentation};
+use textarea::TextareaState;
+use timer::TimerState;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Blink interval of the typer cursor
+const CURSOR_BLINK: Duration = Duration::from_millis(500);
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "rum", about = "Stylish interactive scripts")]
 struct Opts {
-    /// Styling string
-    #[structopt(short("s"), long)]
-    style: Option<String>,
+    /// Styling string, e.g. "fg=red,bg=black,bold,italic"
+    #[structopt(short("s"), long, env = "RUM_STYLE", parse(try_from_str = parse_style))]
+    style: Option<Style>,
 
     /// Viewport height
     #[structopt(short("h"), long)]
@@ -36,11 +71,99 @@ struct Opts {
     #[structopt(short("w"), long, default_value = "32")]
     width: usize,
 
+    /// Write the result to this file instead of stdout
+    #[structopt(short("o"), long, parse(from_os_str))]
+    output: Option<PathBuf>,
+
+    /// Draw below the cursor instead of on the alternate screen
+    #[structopt(long)]
+    no_alt_screen: bool,
+
+    /// Give up after this many seconds, exiting with 124
+    #[structopt(long)]
+    timeout: Option<u64>,
+
+    /// Color theme
+    #[structopt(long, env = "RUM_THEME", possible_values = &ThemeName::variants(), case_insensitive = true, default_value = "default")]
+    theme: ThemeName,
+
+    /// Print the result as a JSON object, with the selections of choose as an array
+    #[structopt(long)]
+    json: bool,
+
+    /// Milliseconds to wait for input between updates, animations may update faster
+    #[structopt(long, default_value = "50")]
+    poll_ms: u64,
+
+    /// Ring the terminal bell on invalid actions
+    #[structopt(long)]
+    bell: bool,
+
+    /// Blank rows and columns above and left of the component
+    #[structopt(long, default_value = "2")]
+    padding: u16,
+
+    /// Box drawn around the component
+    #[structopt(long, possible_values = &Border::variants(), case_insensitive = true, default_value = "none")]
+    border: Border,
+
+    /// Key that quits with exit code 1, e.g. "ctrl-d", "esc" or "q"
+    #[structopt(long, default_value = "ctrl-c", parse(try_from_str = parse_key))]
+    cancel_key: Key,
+
+    /// Extra keys for actions, e.g. "down=j,up=k,submit=ctrl-d". Actions are up, down,
+    /// left, right, home, end, pageup, pagedown, toggle, complete, submit and cancel.
+    /// Keys without ctrl or alt are ignored while typing into text, filter and search fields
+    #[structopt(long, env = "RUM_KEYMAP", parse(try_from_str = parse_keymap))]
+    keymap: Option<Keymap>,
+
+    /// Return the component's default right away, without reading any keys
+    #[structopt(long)]
+    non_interactive: bool,
+
     /// Subcommand
     #[structopt(subcommand)]
     subcommand: Subcommand,
 }
 
+arg_enum! {
+    #[derive(Debug, Clone, Copy)]
+    enum Border {
+        None,
+        Rounded,
+        Thick,
+    }
+}
+
+impl Border {
+    /// Rows and columns taken by the border and the gap inside it, on each side
+    fn inset(self) -> u16 {
+        match self {
+            Border::None => 0,
+            _ => 2,
+        }
+    }
+
+    /// Corners clockwise from the top left, then the horizontal and vertical edges
+    fn chars(self) -> Option<[char; 6]> {
+        match self {
+            Border::None => None,
+            Border::Rounded => Some(['╭', '╮', '╯', '╰', '─', '│']),
+            Border::Thick => Some(['┏', '┓', '┛', '┗', '━', '┃']),
+        }
+    }
+}
+
+arg_enum! {
+    #[derive(Debug, Clone, Copy)]
+    enum ThemeName {
+        Default,
+        Dracula,
+        Solarized,
+        Mono,
+    }
+}
+
 arg_enum! {
     #[derive(Debug)]
     enum SpinnerStyle {
@@ -58,41 +181,183 @@ arg_enum! {
     }
 }
 
+arg_enum! {
+    #[derive(Debug, Clone, Copy)]
+    enum SpinnerAlign {
+        Left,
+        Right,
+    }
+}
+
+arg_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum CursorStyle {
+        Reverse,
+        Block,
+        Bar,
+        Underline,
+        Hidden,
+    }
+}
+
+impl CursorStyle {
+    /// Shape of the terminal's own cursor, if it is used
+    fn shape(self, blink: bool) -> Option<SetCursorStyle> {
+        Some(match (self, blink) {
+            (CursorStyle::Block, false) => SetCursorStyle::SteadyBlock,
+            (CursorStyle::Block, true) => SetCursorStyle::BlinkingBlock,
+            (CursorStyle::Bar, false) => SetCursorStyle::SteadyBar,
+            (CursorStyle::Bar, true) => SetCursorStyle::BlinkingBar,
+            (CursorStyle::Underline, false) => SetCursorStyle::SteadyUnderScore,
+            (CursorStyle::Underline, true) => SetCursorStyle::BlinkingUnderScore,
+            (CursorStyle::Reverse | CursorStyle::Hidden, _) => return None,
+        })
+    }
+}
+
 #[derive(Debug, StructOpt)]
 enum Subcommand {
     /// Single line text input
     #[structopt()]
     Text {
         /// Placeholder text
-        #[structopt(short("p"), long, default_value = "Enter text here")]
+        #[structopt(
+            short("p"),
+            long,
+            env = "RUM_TEXT_PLACEHOLDER",
+            default_value = "Enter text here"
+        )]
         placeholder: String,
 
         /// Prefix
         #[structopt(short("x"), long, default_value = "> ")]
         prefix: String,
+
+        /// Mask the input, for entering secrets. Ctrl-R toggles showing it
+        #[structopt(long)]
+        password: bool,
+
+        /// Masking character used with --password
+        #[structopt(long, default_value = "•")]
+        mask: char,
+
+        /// Maximum number of characters, 0 for unlimited
+        #[structopt(long, default_value = "0")]
+        char_limit: usize,
+
+        /// Initial value
+        #[structopt(long, env = "RUM_TEXT_VALUE", default_value = "")]
+        value: String,
+
+        /// Only submit input matching this regular expression
+        #[structopt(long)]
+        validate: Option<Regex>,
+
+        /// Message shown when the input fails validation
+        #[structopt(long, default_value = "Invalid input")]
+        validate_message: String,
+
+        /// How to draw the cursor, block, bar and underline use the terminal's cursor
+        #[structopt(long, possible_values = &CursorStyle::variants(), case_insensitive = true, default_value = "reverse")]
+        cursor_style: CursorStyle,
+
+        /// Make the terminal's cursor blink
+        #[structopt(long)]
+        cursor_blink: bool,
+
+        /// File of previous inputs to recall with Up and Down, appended to on submit
+        #[structopt(long)]
+        history: Option<PathBuf>,
+
+        /// File of words to complete with Tab, or "-" to read them from stdin
+        #[structopt(long)]
+        complete: Option<PathBuf>,
+
+        /// Show the number of characters entered
+        #[structopt(long)]
+        show_count: bool,
+
+        /// Key that submits the input, e.g. "enter" or "ctrl-d"
+        #[structopt(long, default_value = "enter", parse(try_from_str = parse_key))]
+        submit_on: Key,
+
+        /// Answer shown dimmed after the input while it matches, accepted with Tab or Right
+        #[structopt(long)]
+        suggestion: Option<String>,
+    },
+    /// Multi-line text input
+    #[structopt()]
+    Textarea {
+        /// Placeholder text
+        #[structopt(
+            short("p"),
+            long,
+            env = "RUM_TEXTAREA_PLACEHOLDER",
+            default_value = "Enter text here"
+        )]
+        placeholder: String,
+
+        /// Submit with Ctrl and this key
+        #[structopt(long, default_value = "d")]
+        submit_key: char,
     },
     /// Binary confirmation input
     #[structopt()]
     Confirm {
         /// Title text
-        #[structopt(short("t"), long, default_value = "Confirm?")]
+        #[structopt(short("t"), long, env = "RUM_CONFIRM_TEXT", default_value = "Confirm?")]
         text: String,
 
         /// No option text
-        #[structopt(short("n"), long, default_value = "No")]
+        #[structopt(short("n"), long, env = "RUM_CONFIRM_NO", default_value = "No")]
         no: String,
 
         /// Yes option text
-        #[structopt(short("y"), long, default_value = "Yes")]
+        #[structopt(short("y"), long, env = "RUM_CONFIRM_YES", default_value = "Yes")]
         yes: String,
+
+        /// Select the yes option by default. Without it, RUM_CONFIRM_DEFAULT=yes or no
+        /// picks the default when there are no options or required text
+        #[structopt(long)]
+        default_yes: bool,
+
+        /// Submit the current selection after this many seconds
+        #[structopt(long)]
+        timeout: Option<u64>,
+
+        /// Print the chosen option's text as well as setting the exit code
+        #[structopt(long)]
+        print: bool,
+
+        /// Comma separated buttons to pick from instead of yes and no.
+        /// The chosen one is printed and the exit code is 0
+        #[structopt(long, use_delimiter = true, conflicts_with = "default-yes")]
+        options: Vec<String>,
+
+        /// Text that has to be typed before yes can be chosen
+        #[structopt(long, conflicts_with_all = &["default-yes", "options"])]
+        require_text: Option<String>,
+
+        /// Submit as soon as Left or Right picks an option, without Enter
+        #[structopt(long)]
+        instant: bool,
     },
     /// Spinner progress indicator
     #[structopt()]
     Spinner {
         /// Text
-        #[structopt(short("t"), long, default_value = "Waiting ...")]
+        #[structopt(
+            short("t"),
+            long,
+            env = "RUM_SPINNER_TEXT",
+            default_value = "Waiting ..."
+        )]
         text: String,
 
+        /// Bold title shown on the line above the spinner
+        #[structopt(long)]
+        title: Option<String>,
+
         /// Spinner speed, milliseconds between frames
         #[structopt(short("i"), long, default_value = "100")]
         speed: usize,
@@ -101,6 +366,66 @@ enum Subcommand {
         #[structopt(short("s"), long, possible_values = &SpinnerStyle::variants(), case_insensitive = true, default_value = "braille")]
         spinner_style: SpinnerStyle,
 
+        /// Comma-separated animation frames, overriding the spinner style
+        #[structopt(long)]
+        frames: Option<String>,
+
+        /// Spinner placement relative to the text
+        #[structopt(long, possible_values = &SpinnerAlign::variants(), case_insensitive = true, default_value = "left")]
+        align: SpinnerAlign,
+
+        /// Show the last lines of the command's output
+        #[structopt(long)]
+        show_output: bool,
+
+        /// Print the command's output once it succeeds
+        #[structopt(long)]
+        print_output: bool,
+
+        /// Kill the command after this many seconds, exiting with 124
+        #[structopt(long)]
+        timeout: Option<u64>,
+
+        /// Show the elapsed time
+        #[structopt(long)]
+        show_time: bool,
+
+        /// Message shown after the command succeeds
+        #[structopt(long)]
+        success_message: Option<String>,
+
+        /// Message shown after the command fails
+        #[structopt(long)]
+        error_message: Option<String>,
+
+        /// Stop the command and succeed once a line of its output matches this regex
+        #[structopt(long)]
+        until: Option<Regex>,
+
+        /// Run the command through `sh -c`, allowing pipes, redirects and globs.
+        /// Arguments are joined with spaces and subject to shell expansion,
+        /// so only use this with trusted input
+        #[structopt(long)]
+        shell: bool,
+
+        /// Write started, frame and exited events to this file descriptor as JSON lines
+        #[structopt(long)]
+        status_fd: Option<u32>,
+
+        /// Print the command's stderr if it fails, instead of letting it through
+        #[structopt(long)]
+        capture_stderr: bool,
+
+        /// Command to run after the previous one succeeds, split on whitespace
+        /// unless --shell is given. Can be repeated
+        #[structopt(long, number_of_values = 1)]
+        then: Vec<String>,
+
+        /// Shell command run between frames, one at a time. The last line
+        /// of its output replaces the text, e.g. to show "(3/10 files)"
+        #[structopt(long)]
+        on_tick: Option<String>,
+
         /// The subcommand to spawn a child process
         #[structopt(name = "COMMAND", required = true)]
         command: Vec<String>,
@@ -112,13 +437,23 @@ enum Subcommand {
         speed: usize,
         #[structopt(short("w"), long, default_value = "1000")]
         wait: usize,
+        /// Text to type, read from stdin if not given
         #[structopt(short("t"), long)]
-        text: String,
+        text: Option<String>,
+        /// Start over after waiting, until a key is pressed
+        #[structopt(short("l"), long("loop"))]
+        looping: bool,
+        /// Text color
+        #[structopt(short("c"), long, parse(try_from_str = parse_color))]
+        color: Option<Color>,
+        /// Bold text
+        #[structopt(short("b"), long)]
+        bold: bool,
     },
     /// Choose from a few different options
     #[structopt()]
     Choose {
-        /// Number of allowed selections
+        /// Number of allowed selections, past which the oldest selection is replaced
         #[structopt(short("s"), long, default_value = "1")]
         selections: NonZeroUsize,
 
@@ -126,8 +461,166 @@ enum Subcommand {
         #[structopt(short("i"), long)]
         inexact: bool,
 
+        /// Minimum number of selections, overriding --inexact
+        #[structopt(long)]
+        min: Option<usize>,
+
+        /// Maximum number of selections, overriding --selections
+        #[structopt(long)]
+        max: Option<NonZeroUsize>,
+
+        /// Text
+        #[structopt(
+            short("t"),
+            long,
+            env = "RUM_CHOOSE_TEXT",
+            default_value = "Choose from these options:"
+        )]
+        text: String,
+
+        /// Type to filter the options, toggling selection with Tab
+        #[structopt(short("f"), long)]
+        filter: bool,
+
+        /// Output selections in input order rather than selection order
+        #[structopt(long)]
+        ordered: bool,
+
+        /// Read options separated by NUL instead of newlines
+        #[structopt(long)]
+        read0: bool,
+
+        /// Separator between selections, understands \n, \t and \0
+        #[structopt(long, default_value = "\\n", parse(from_str = parse_delimiter))]
+        output_delimiter: String,
+
+        /// Output the zero-based indices of the selections instead of their values.
+        /// Indices count options only, not headers or skipped blank lines
+        #[structopt(long)]
+        print_index: bool,
+
+        /// Treat lines starting with this prefix as section headers
+        #[structopt(long)]
+        header_prefix: Option<String>,
+
+        /// Read the options from this file instead of stdin
+        #[structopt(long)]
+        options_file: Option<PathBuf>,
+
+        /// Zero-based index of the option the cursor starts on, counting options only
+        #[structopt(long)]
+        initial_index: Option<usize>,
+
+        /// Comma separated zero-based indices of options to select initially,
+        /// counting options only
+        #[structopt(long, use_delimiter = true)]
+        selected: Vec<usize>,
+
+        /// Refuse selections past the maximum instead of replacing the oldest one
+        #[structopt(long)]
+        strict: bool,
+
+        /// Click to toggle options and scroll with the mouse wheel
+        #[structopt(long)]
+        mouse: bool,
+
+        /// Shown before the option under the cursor
+        #[structopt(long, default_value = "> ")]
+        cursor_prefix: String,
+
+        /// Exit with this code when nothing is selected
+        #[structopt(long)]
+        empty_code: Option<u8>,
+
+        /// Keep blank options instead of skipping them
+        #[structopt(long)]
+        keep_empty: bool,
+
+        /// Return values from the lines of this file, one per option in order, instead of the labels
+        #[structopt(long)]
+        values_from: Option<PathBuf>,
+
+        /// Keep reading options from stdin while choosing
+        #[structopt(
+            long,
+            conflicts_with_all = &["read0", "options-file", "initial-index", "selected", "values-from"]
+        )]
+        live: bool,
+    },
+    /// Fuzzy search the options and pick one
+    #[structopt()]
+    Filter {
+        /// Prefix
+        #[structopt(short("x"), long, default_value = "> ")]
+        prefix: String,
+
+        /// Read options separated by NUL instead of newlines
+        #[structopt(long)]
+        read0: bool,
+
+        /// Read the options from this file instead of stdin
+        #[structopt(long)]
+        options_file: Option<PathBuf>,
+    },
+    /// Scroll through text from stdin
+    #[structopt()]
+    Pager,
+    /// Progress bar driven by percentages on stdin
+    #[structopt()]
+    Progress,
+    /// Pick one option, with "&" marking the hotkey that selects it immediately
+    #[structopt()]
+    Menu {
+        /// Text
+        #[structopt(short("t"), long, default_value = "Choose an option:")]
+        text: String,
+    },
+    /// Count down, finishing early on any key
+    #[structopt()]
+    Timer {
+        /// Seconds to count down from
+        #[structopt(short("d"), long)]
+        duration: u64,
+
+        /// Text
+        #[structopt(short("t"), long, default_value = "Time remaining:")]
+        text: String,
+    },
+    /// Count up until Enter or Space is pressed, printing the elapsed time
+    #[structopt()]
+    Stopwatch {
+        /// Text
+        #[structopt(short("t"), long, default_value = "Elapsed:")]
+        text: String,
+    },
+    /// Pick a number in a range
+    #[structopt()]
+    Slider {
+        /// Text
+        #[structopt(short("t"), long, default_value = "Pick a number:")]
+        text: String,
+
+        /// Smallest value
+        #[structopt(long, default_value = "0", allow_hyphen_values = true)]
+        min: f64,
+
+        /// Largest value
+        #[structopt(long, default_value = "100", allow_hyphen_values = true)]
+        max: f64,
+
+        /// Amount each key press changes the value by
+        #[structopt(long, default_value = "1")]
+        step: f64,
+
+        /// Initial value, defaults to the minimum
+        #[structopt(long, allow_hyphen_values = true)]
+        value: Option<f64>,
+    },
+    /// Wait for any key
+    #[structopt()]
+    Pause {
         /// Text
-        #[structopt(short("t"), long, default_value = "Choose from these options:")]
+        #[structopt(short("t"), long, default_value = "Press any key to continue")]
         text: String,
     },
 }
@@ -142,545 +635,3598 @@ impl<V, E> DropError<V> for Result<V, E> {
     }
 }
 
-fn get_bg_color(active: bool) -> Color {
-    if active {
-        Color::Magenta
-    } else {
-        Color::DarkGrey
-    }
+/// Byte index of the `n`th grapheme of `s`, or `s.len()` if out of range
+fn grapheme_byte_index(s: &str, n: usize) -> usize {
+    s.grapheme_indices(true)
+        .nth(n)
+        .map(|(i, _)| i)
+        .unwrap_or(s.len())
 }
 
-#[derive(Debug, Default)]
-struct TextState {
-    input: String,
+/// Byte index past the leading graphemes of `s` that fit in `columns`
+fn column_byte_index(s: &str, columns: usize) -> usize {
+    let mut used = 0;
+    for (i, g) in s.grapheme_indices(true) {
+        used += g.width();
+        if used > columns {
+            return i;
+        }
+    }
+    s.len()
 }
 
-#[derive(Debug, Default)]
-struct ConfirmState {
-    confirmed: bool,
+/// Cut `s` to fit in `width` columns, ending with '…' if anything was cut
+fn truncate(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return s.to_owned();
+    }
+    let mut out = String::new();
+    let mut columns = 0;
+    for g in s.graphemes(true) {
+        columns += g.width();
+        if columns + 1 > width {
+            break;
+        }
+        out.push_str(g);
+    }
+    out.push('…');
+    out
 }
 
-#[derive(Debug)]
-struct SpinnerState {
-    child: Child,
-    chars: Vec<String>,
-    progress: usize,
-    last_updated: Instant,
+/// Columns left for a choose label in `width` after the prefix and selection marker
+fn label_width(width: usize, cursor_prefix: &str, selection: &str) -> usize {
+    width.saturating_sub(cursor_prefix.width() + selection.width() + 1)
 }
 
-#[derive(Debug)]
-struct ChooseState {
-    choices: Vec<String>,
-    chosen: LruCache<usize, ()>,
-    selections: NonZeroUsize,
-    cursor_loc: usize,
+/// Word wrap `text` into lines of at most `width` columns.
+/// Words longer than `width` are broken up.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut len = 0;
+        for word in paragraph.split_word_bounds() {
+            if len + word.width() > width && len > 0 {
+                lines.push(line.trim_end().to_owned());
+                line.clear();
+                len = 0;
+            }
+            // don't start a line with whitespace
+            if len == 0 && word.trim().is_empty() {
+                continue;
+            }
+            for g in word.graphemes(true) {
+                if len + g.width() > width && len > 0 {
+                    lines.push(std::mem::take(&mut line));
+                    len = 0;
+                }
+                line.push_str(g);
+                len += g.width();
+            }
+        }
+        lines.push(line);
+    }
+    lines
 }
 
-#[derive(Debug)]
-struct TyperState<'a> {
-    iter: Graphemes<'a>,
-    done_printing: bool,
-    last_updated: Instant,
+/// Read all lines from `reader`, or NUL separated entries if `read0` is set
+fn read_entries<R: BufRead>(reader: R, read0: bool) -> Result<Vec<String>, String> {
+    if !read0 {
+        return reader
+            .lines()
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string());
+    }
+    reader
+        .split(b'\0')
+        .map(|entry| {
+            let entry = entry.map_err(|e| e.to_string())?;
+            String::from_utf8(entry).map_err(|e| e.to_string())
+        })
+        .collect()
 }
 
-enum Component<'a> {
-    Text {
-        width: usize,
-        placeholder: String,
-        prefix: String,
-        state: TextState,
-    },
-    Confirm {
-        text: String,
-        padded_no: String,
-        padded_yes: String,
-        state: ConfirmState,
-    },
-    Spinner {
-        speed: Duration,
-        text: String,
-        state: SpinnerState,
-    },
-    Typer {
-        speed: Duration,
-        wait: Duration,
-        text: String,
-        state: TyperState<'a>,
-    },
-    Choose {
-        text: String,
-        selected_string: String,
-        unselected_string: String,
-        inexact: bool,
-        state: ChooseState,
-    },
+/// Read all lines from stdin, or NUL separated entries if `read0` is set
+fn read_stdin_lines(read0: bool) -> Result<Vec<String>, String> {
+    read_entries(stdin().lock(), read0)
 }
 
-impl<'a> Component<'a> {
-    pub fn from_opts(opts: &Opts) -> Component {
-        match &opts.subcommand {
-            Subcommand::Text {
-                placeholder,
-                prefix,
-            } => Component::Text {
-                width: opts.width,
-                placeholder: placeholder.clone(),
-                prefix: prefix.clone(),
-                state: TextState::default(),
-            },
-            Subcommand::Confirm { text, no, yes } => {
-                let no = no.clone();
-                let yes = yes.clone();
-                let padded_no = format!("{: ^10}", no);
-                let padded_yes = format!("{: ^10}", yes);
+/// Read the options from `path` if given, otherwise from stdin
+fn read_options(path: Option<&PathBuf>, read0: bool) -> Result<Vec<String>, String> {
+    match path {
+        Some(path) => {
+            let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            read_entries(BufReader::new(file), read0)
+                .map_err(|e| format!("{}: {e}", path.display()))
+        }
+        None => read_stdin_lines(read0),
+    }
+}
 
-                Component::Confirm {
-                    text: text.clone(),
-                    padded_no,
-                    padded_yes,
-                    state: ConfirmState::default(),
-                }
+/// Read lines from `reader` on a background thread
+fn read_lines<R: Read + Send + 'static>(reader: R) -> Receiver<String> {
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
             }
-            Subcommand::Spinner {
-                text,
-                speed,
-                command,
-                spinner_style,
-            } => {
-                let chars: Vec<String> = match spinner_style {
-                    SpinnerStyle::Braille => vec!["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"],
-                    SpinnerStyle::VBar => vec![
-                        "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█", "▇", "▆", "▅", "▄", "▃", "▂", "▁",
-                    ],
-                    SpinnerStyle::Arrow => vec!["←", "↖", "↑", "↗", "→", "↘", "↓", "↙"],
-                    SpinnerStyle::Circle => vec!["◜", "◠", "◝", "◞", "◡", "◟"],
-                    SpinnerStyle::Pulse => vec!["█", "▓", "▒", "░"],
-                    SpinnerStyle::Line => vec!["|", "/", "-", "\\"],
-                    SpinnerStyle::Moon => vec![
-                        "\u{1f311}",
-                        "\u{1f312}",
-                        "\u{1f313}",
-                        "\u{1f314}",
-                        "\u{1f315}",
-                        "\u{1f316}",
-                        "\u{1f317}",
-                        "\u{1f318}",
-                    ],
-                    SpinnerStyle::Monkey => vec!["\u{1f648}", "\u{1f649}", "\u{1f64a}"],
-                    SpinnerStyle::Meter => vec!["▱▱▱", "▰▱▱", "▰▰▱", "▰▰▰", "▰▰▱", "▰▱▱", "▱▱▱"],
-                    SpinnerStyle::Points => vec!["∙∙∙", "●∙∙", "∙●∙", "∙∙●"],
-                    SpinnerStyle::Progress => vec![
-                        "[     ]", "[>    ]", "[=>   ]", "[==>  ]", "[===> ]", "[====>]", "[=====]",
-                    ],
-                }
-                .iter()
-                .map(|e| e.to_string())
-                .collect();
+        }
+    });
+    rx
+}
 
-                let child = Command::new(&command[0])
-                    .args(&command[1..])
-                    .stdout(Stdio::null())
-                    .spawn()
-                    .unwrap();
-                Component::Spinner {
-                    text: text.clone(),
-                    state: SpinnerState {
-                        chars: chars.to_owned(),
-                        last_updated: Instant::now(),
-                        progress: 0,
-                        child,
-                    },
-                    speed: Duration::from_millis(*speed as u64),
-                }
-            }
-            Subcommand::Typer { speed, text, wait } => Component::Typer {
-                speed: Duration::from_millis(*speed as u64),
-                wait: Duration::from_millis(*wait as u64),
-                text: text.clone(),
-                state: TyperState {
-                    iter: text.graphemes(true),
+/// Start a command of the spinner, reading its stdout if `piped`
+/// and its stderr if `capture_stderr`
+fn spawn(command: &mut Command, piped: bool, capture_stderr: bool) -> Result<Child, String> {
+    command
+        .stdout(if piped { Stdio::piped() } else { Stdio::null() })
+        .stderr(if capture_stderr {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .spawn()
+        .map_err(|e| format!("{}: {e}", command.get_program().to_string_lossy()))
+}
+
+/// Run `command` through `sh -c` on a thread, sending the last line of its output
+/// if it succeeds
+fn probe(command: &str) -> Receiver<String> {
+    let (tx, rx) = channel();
+    let mut sh = Command::new("sh");
+    sh.arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null());
+    thread::spawn(move || {
+        let Ok(output) = sh.output() else { return };
+        if !output.status.success() {
+            return;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if let Some(line) = stdout.lines().rfind(|line| !line.trim().is_empty()) {
+            tx.send(line.to_owned()).ok(); // swallow error
+        }
+    });
+    rx
+}
+
+/// Duplicate the inherited descriptor `fd`, keeping its offset and append mode
+fn status_file(fd: u32) -> Result<File, String> {
+    let error = |e: io::Error| format!("Status fd {fd}: {e}");
+    let raw = RawFd::try_from(fd).map_err(|_| format!("Status fd {fd}: out of range"))?;
+    // borrowing requires an open descriptor
+    fs::metadata(format!("/dev/fd/{fd}")).map_err(error)?;
+    // SAFETY: the descriptor was just checked to be open, and is only borrowed to dup it
+    let borrowed = unsafe { BorrowedFd::borrow_raw(raw) };
+    borrowed.try_clone_to_owned().map(File::from).map_err(error)
+}
+
+fn parse_color(s: &str) -> Result<Color, String> {
+    Color::try_from(s).map_err(|_| format!("Unknown color '{s}'"))
+}
+
+/// Unescape \n, \t, \0 and \\ so delimiters can be given on the command line
+fn parse_delimiter(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('0') => out.push('\0'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// The terminal that components draw to
+struct Screen {
+    /// Drawing is queued here and flushed once per frame
+    out: BufWriter<Stderr>,
+    /// First row of the drawing area
+    origin: u16,
+    /// Blank rows and columns before the component
+    padding: u16,
+    /// Whether invalid actions ring the terminal bell
+    bell: bool,
+    border: Border,
+}
+
+impl Screen {
+    /// Ring the bell, if enabled
+    fn ring(&mut self) -> Result<(), ()> {
+        if self.bell {
+            queue!(self, Print('\x07')).drop_error()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        // a half drawn frame would land on top of the panic message
+        if thread::panicking() {
+            let out = std::mem::replace(&mut self.out, BufWriter::with_capacity(0, stderr()));
+            let _ = out.into_parts();
+        }
+    }
+}
+
+/// Draw a box from `(left, top)` around content of `columns` by `rows`,
+/// leaving a blank cell between them
+fn draw_border(
+    screen: &mut Screen,
+    (left, top): (u16, u16),
+    (columns, rows): (usize, usize),
+) -> Result<(), ()> {
+    let Some([top_left, top_right, bottom_right, bottom_left, horizontal, vertical]) =
+        screen.border.chars()
+    else {
+        return Ok(());
+    };
+    let (columns, rows) = (columns as u16 + 2, rows as u16 + 2);
+    let edge = horizontal.to_string().repeat(columns as usize);
+    queue!(
+        screen,
+        MoveTo(left, top),
+        Print(format!("{top_left}{edge}{top_right}")),
+        MoveTo(left, top + rows + 1),
+        Print(format!("{bottom_left}{edge}{bottom_right}"))
+    )
+    .drop_error()?;
+    for row in top + 1..=top + rows {
+        queue!(
+            screen,
+            MoveTo(left, row),
+            Print(vertical),
+            MoveTo(left + columns + 1, row),
+            Print(vertical)
+        )
+        .drop_error()?;
+    }
+    Ok(())
+}
+
+/// Query the cursor row, reading the answer from the terminal rather than stdin,
+/// which may be piped. Requires raw mode.
+fn cursor_row(screen: &mut Screen) -> Result<u16, ()> {
+    let mut tty = File::open("/dev/tty").drop_error()?;
+    execute!(screen, Print("\x1b[6n")).drop_error()?;
+
+    // answer is ESC [ row ; column R
+    let mut answer = vec![];
+    let mut byte = [0];
+    while answer.last() != Some(&b'R') {
+        tty.read_exact(&mut byte).drop_error()?;
+        answer.push(byte[0]);
+    }
+    let answer = String::from_utf8_lossy(&answer);
+    let (_, row) = answer.split_once('[').ok_or(())?;
+    let (row, _) = row.split_once(';').ok_or(())?;
+    let row: u16 = row.parse().drop_error()?;
+    Ok(row.saturating_sub(1))
+}
+
+impl Write for Screen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.out.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Colors and attributes applied to the primary text of a component
+#[derive(Debug, Default, Clone)]
+struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    attributes: Vec<Attribute>,
+}
+
+impl Style {
+    fn apply(&self, screen: &mut Screen) -> Result<(), ()> {
+        if let Some(fg) = self.fg {
+            queue!(screen, SetForegroundColor(fg)).drop_error()?;
+        }
+        if let Some(bg) = self.bg {
+            queue!(screen, SetBackgroundColor(bg)).drop_error()?;
+        }
+        for attribute in &self.attributes {
+            queue!(screen, SetAttribute(*attribute)).drop_error()?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_style(s: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        match part.split_once('=') {
+            Some(("fg", color)) => style.fg = Some(parse_color(color)?),
+            Some(("bg", color)) => style.bg = Some(parse_color(color)?),
+            Some(_) => return Err(format!("Unknown style '{part}'")),
+            None => style.attributes.push(match part {
+                "bold" => Attribute::Bold,
+                "dim" => Attribute::Dim,
+                "italic" => Attribute::Italic,
+                "underline" => Attribute::Underlined,
+                "reverse" => Attribute::Reverse,
+                _ => return Err(format!("Unknown attribute '{part}'")),
+            }),
+        }
+    }
+    Ok(style)
+}
+
+/// A key with modifiers, e.g. "ctrl-c" or "esc"
+#[derive(Debug, Clone, Copy)]
+struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn matches(&self, event: &Event) -> bool {
+        let Event::Key(key) = event else {
+            return false;
+        };
+        // shift is implied by the character itself
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+        key.code == self.code && modifiers == self.modifiers
+    }
+
+    /// Whether this is a character typed without ctrl or alt
+    fn is_plain(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_))
+            && !self
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+
+    /// A press of this key
+    fn event(&self) -> Event {
+        Event::Key(KeyEvent::new(self.code, self.modifiers))
+    }
+}
+
+fn parse_key(s: &str) -> Result<Key, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = s;
+    // a trailing '-' is the key itself, as in "ctrl--"
+    while let Some((modifier, rest)) = name.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return Err(format!("Unknown modifier '{modifier}'")),
+        };
+        name = rest;
+    }
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match name.to_lowercase().as_str() {
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ => return Err(format!("Unknown key '{name}'")),
+        },
+    };
+    Ok(Key { code, modifiers })
+}
+
+/// Something a key can be bound to with --keymap
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Toggle,
+    Complete,
+    Submit,
+    Cancel,
+}
+
+/// Keys bound to actions
+#[derive(Debug)]
+struct Keymap(Vec<(Key, Action)>);
+
+impl Keymap {
+    /// The action bound to `event`. Keys without ctrl or alt are left alone
+    /// while `typing`, so they can still be typed
+    fn action(&self, event: &Event, typing: bool) -> Option<Action> {
+        self.0
+            .iter()
+            .filter(|(key, _)| !(typing && key.is_plain()))
+            .find(|(key, _)| key.matches(event))
+            .map(|(_, action)| *action)
+    }
+}
+
+/// Parse bindings like "down=j,up=k,submit=ctrl-d"
+fn parse_keymap(s: &str) -> Result<Keymap, String> {
+    let mut bindings = vec![];
+    for binding in s.split(',').filter(|binding| !binding.is_empty()) {
+        let Some((action, key)) = binding.split_once('=') else {
+            return Err(format!("Expected action=key, got '{binding}'"));
+        };
+        let action = match action.trim().to_lowercase().as_str() {
+            "up" => Action::Up,
+            "down" => Action::Down,
+            "left" => Action::Left,
+            "right" => Action::Right,
+            "home" => Action::Home,
+            "end" => Action::End,
+            "pageup" => Action::PageUp,
+            "pagedown" => Action::PageDown,
+            "toggle" => Action::Toggle,
+            "complete" => Action::Complete,
+            "submit" => Action::Submit,
+            "cancel" => Action::Cancel,
+            _ => return Err(format!("Unknown action '{action}'")),
+        };
+        bindings.push((parse_key(key.trim())?, action));
+    }
+    Ok(Keymap(bindings))
+}
+
+/// Quote and escape `s` as a JSON string
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Format a duration as mm:ss
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// First line of a block of `content_height` rows, centered within `height` rows if given
+fn start_line(top: u16, content_height: usize, height: Option<usize>) -> u16 {
+    top + height.map_or(0, |height| height.saturating_sub(content_height) / 2) as u16
+}
+
+/// Colors of active and inactive elements
+#[derive(Debug, Clone, Copy)]
+struct Theme {
+    active: Color,
+    inactive: Color,
+}
+
+impl Theme {
+    fn color(&self, active: bool) -> Color {
+        if active {
+            self.active
+        } else {
+            self.inactive
+        }
+    }
+}
+
+impl From<ThemeName> for Theme {
+    fn from(name: ThemeName) -> Self {
+        let (active, inactive) = match name {
+            ThemeName::Default => (Color::Magenta, Color::DarkGrey),
+            ThemeName::Dracula => (Color::from((189, 147, 249)), Color::from((68, 71, 90))),
+            ThemeName::Solarized => (Color::from((38, 139, 210)), Color::from((88, 110, 117))),
+            ThemeName::Mono => (Color::Grey, Color::DarkGrey),
+        };
+        Theme { active, inactive }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TextState {
+    input: String,
+    /// Grapheme index of the insertion point
+    cursor: usize,
+    /// Grapheme index of the first visible grapheme
+    offset: usize,
+    /// Validation error shown below the input
+    error: Option<String>,
+    /// Previous inputs, oldest first
+    history: Vec<String>,
+    /// Index into `history`, or its length while editing a new input
+    history_index: usize,
+    /// The new input, kept while browsing the history
+    draft: String,
+    /// Candidates being cycled through by repeated Tabs
+    completion: Option<Completion>,
+    /// Whether masked input is temporarily shown
+    revealed: bool,
+    /// Maximum number of graphemes, 0 for unlimited
+    char_limit: usize,
+}
+
+#[derive(Debug)]
+struct Completion {
+    /// Byte index of the start of the word being completed
+    start: usize,
+    matches: Vec<String>,
+    /// Index into `matches` of the current candidate
+    index: usize,
+}
+
+impl TextState {
+    /// Replace the input with history entry `index`, or the draft past the end
+    fn recall(&mut self, index: usize) {
+        if self.history_index == self.history.len() {
+            self.draft = self.input.clone();
+        }
+        self.history_index = index;
+        self.input = self.history.get(index).unwrap_or(&self.draft).clone();
+        self.cursor = self.input.graphemes(true).count();
+        self.limit();
+    }
+
+    /// Cut the input down to the character limit
+    fn limit(&mut self) {
+        if self.char_limit == 0 {
+            return;
+        }
+        let end = grapheme_byte_index(&self.input, self.char_limit);
+        self.input.truncate(end);
+        self.cursor = self.cursor.min(self.char_limit);
+    }
+
+    /// Byte index of the start of the word before the cursor
+    fn word_start(&self) -> usize {
+        let end = grapheme_byte_index(&self.input, self.cursor);
+        self.input[..end]
+            .split_word_bound_indices()
+            .rev()
+            .find(|(_, word)| !word.trim().is_empty())
+            .map_or(0, |(i, _)| i)
+    }
+
+    /// Byte index of the end of the word after the cursor
+    fn word_end(&self) -> usize {
+        let start = grapheme_byte_index(&self.input, self.cursor);
+        self.input[start..]
+            .split_word_bound_indices()
+            .find(|(_, word)| !word.trim().is_empty())
+            .map_or(self.input.len(), |(i, word)| start + i + word.len())
+    }
+
+    /// Move the cursor to byte `index`, returning whether it moved
+    fn jump(&mut self, index: usize) -> bool {
+        let cursor = self.input[..index].graphemes(true).count();
+        let changed = cursor != self.cursor;
+        self.cursor = cursor;
+        changed
+    }
+
+    /// The rest of `suggestion` after the input, as much as the character limit allows,
+    /// while the cursor is at the end
+    fn ghost<'s>(&self, suggestion: &'s str) -> Option<&'s str> {
+        let rest = suggestion.strip_prefix(self.input.as_str())?;
+        let count = self.input.graphemes(true).count();
+        let rest = match self.char_limit {
+            0 => rest,
+            limit => &rest[..grapheme_byte_index(rest, limit.saturating_sub(count))],
+        };
+        (self.cursor == count && !rest.is_empty()).then_some(rest)
+    }
+
+    /// Complete the input with the rest of `suggestion`, returning whether it changed
+    fn accept(&mut self, suggestion: Option<&str>) -> bool {
+        let Some(rest) = suggestion.and_then(|suggestion| self.ghost(suggestion)) else {
+            return false;
+        };
+        self.input.push_str(rest);
+        self.cursor = self.input.graphemes(true).count();
+        true
+    }
+
+    /// Replace the text from byte `start` up to the cursor, moving the cursor after it
+    fn replace_word(&mut self, start: usize, word: &str) {
+        let end = grapheme_byte_index(&self.input, self.cursor);
+        self.input.replace_range(start..end, word);
+        self.cursor = self.input[..start + word.len()].graphemes(true).count();
+        self.limit();
+    }
+
+    /// Complete the word before the cursor from `words`, extending it to the longest
+    /// common prefix of the candidates first, then cycling through them.
+    /// Returns whether the input changed
+    fn complete(&mut self, words: &[String], completing: Option<Completion>) -> bool {
+        if let Some(mut completion) = completing {
+            completion.index = (completion.index + 1) % completion.matches.len();
+            self.replace_word(completion.start, &completion.matches[completion.index]);
+            self.completion = Some(completion);
+            return true;
+        }
+
+        let end = grapheme_byte_index(&self.input, self.cursor);
+        let start = self.input[..end]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &self.input[start..end];
+        let matches: Vec<String> = words
+            .iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .cloned()
+            .collect();
+
+        let Some(first) = matches.first() else {
+            return false;
+        };
+        let common = matches.iter().fold(first.as_str(), |common, candidate| {
+            let len = common
+                .char_indices()
+                .zip(candidate.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.len().min(candidate.len()), |((i, _), _)| i);
+            &common[..len]
+        });
+
+        if common.len() > word.len() {
+            let common = common.to_owned();
+            self.replace_word(start, &common);
+        } else {
+            let first = first.clone();
+            self.replace_word(start, &first);
+            self.completion = Some(Completion {
+                start,
+                matches,
+                index: 0,
+            });
+        }
+        true
+    }
+}
+
+#[derive(Debug)]
+struct ConfirmState {
+    /// Index of the highlighted button
+    selected: usize,
+    started: Instant,
+    /// Seconds left before the timeout, as last drawn
+    countdown: u64,
+    /// Input checked against `require_text`
+    typed: String,
+}
+
+#[derive(Debug)]
+struct SpinnerState {
+    child: Child,
+    chars: Vec<String>,
+    progress: usize,
+    last_updated: Instant,
+    started: Instant,
+    timed_out: bool,
+    /// Whether the output matched `until`
+    matched: bool,
+    /// Lines read from the child's stdout, if piped
+    output: Option<Receiver<String>>,
+    lines: Vec<String>,
+    /// Lines read from the child's stderr, if captured
+    errors: Option<Receiver<String>>,
+    /// Where lifecycle events are reported
+    status: Option<File>,
+    /// Commands left to run after `child`
+    steps: VecDeque<Command>,
+    /// One-based number of the running command, out of `total`
+    step: usize,
+    total: usize,
+    /// Whether the commands' output is read
+    piped: bool,
+    capture_stderr: bool,
+    /// Why the next command could not be started
+    spawn_error: Option<String>,
+    /// Output of the running --on-tick command
+    probe: Option<Receiver<String>>,
+}
+
+impl SpinnerState {
+    /// Write an event like `{"event": "frame"}` to the status fd, if any
+    fn report(&mut self, event: &str, code: Option<u8>) {
+        let Some(status) = &mut self.status else {
+            return;
+        };
+        let code = code.map_or(String::new(), |code| format!(", \"code\": {code}"));
+        writeln!(status, "{{\"event\": {}{code}}}", json_string(event)).ok(); // swallow error
+    }
+
+    /// Start the next command once `child` has exited, returning whether one was started.
+    /// Stops at the first failure or after the last command
+    fn advance(&mut self, success: bool) -> bool {
+        let Some(mut next) = self.steps.pop_front().filter(|_| success) else {
+            return false;
+        };
+        // keep the output of earlier commands
+        if let Some(output) = self.output.take() {
+            self.lines.extend(output.iter());
+        }
+        match spawn(&mut next, self.piped, self.capture_stderr) {
+            Ok(mut child) => {
+                self.output = child.stdout.take().map(read_lines);
+                self.errors = child.stderr.take().map(read_lines);
+                self.child = child;
+                self.step += 1;
+                true
+            }
+            Err(message) => {
+                self.spawn_error = Some(message);
+                false
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ChooseState {
+    /// Displayed labels
+    choices: Vec<String>,
+    /// Returned values, parallel to `choices`
+    values: Vec<String>,
+    /// Whether each choice is a section header, parallel to `choices`
+    headers: Vec<bool>,
+    chosen: LruCache<usize, ()>,
+    /// Maximum number of selections
+    selections: NonZeroUsize,
+    /// Minimum number of selections
+    min: usize,
+    /// Index into `visible`
+    cursor_loc: usize,
+    /// First visible row
+    offset: usize,
+    query: String,
+    /// Indices of the choices matching `query`
+    visible: Vec<usize>,
+    /// Message shown in place of the hint until the next key
+    error: Option<String>,
+    /// Screen row of the first visible option, as last drawn
+    list_top: u16,
+    /// Lines starting with this are section headers
+    header_prefix: Option<String>,
+    keep_empty: bool,
+    /// Lines still arriving on stdin, with --live
+    feed: Option<Receiver<String>>,
+}
+
+impl ChooseState {
+    /// Index of the choice under the cursor
+    fn current(&self) -> Option<usize> {
+        self.visible
+            .get(self.cursor_loc)
+            .copied()
+            .filter(|i| !self.headers[*i])
+    }
+
+    /// Indices of the choices that are options rather than headers
+    fn options(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.choices.len()).filter(|i| !self.headers[*i])
+    }
+
+    /// Values of the chosen items, or their indices with `print_index`,
+    /// in selection order or input order if `ordered`
+    fn selected(&self, ordered: bool, print_index: bool) -> Vec<String> {
+        let indices: Vec<usize> = if ordered {
+            (0..self.values.len())
+                .filter(|i| self.chosen.contains(i))
+                .collect()
+        } else {
+            self.chosen.iter().map(|(k, _)| *k).collect()
+        };
+        indices
+            .iter()
+            .filter_map(|k| {
+                if print_index {
+                    // numbered among the options, like --selected and --initial-index
+                    self.options().position(|i| i == *k).map(|i| i.to_string())
+                } else {
+                    self.values.get(*k).map(ToOwned::to_owned)
+                }
+            })
+            .collect()
+    }
+
+    /// Select or deselect the choice under the cursor. Past the maximum,
+    /// the oldest selection is replaced, or with `strict` an error is shown
+    fn toggle(&mut self, strict: bool) {
+        let Some(current) = self.current() else {
+            return;
+        };
+        if self.chosen.contains(&current) {
+            self.chosen.pop(&current);
+        } else if strict && self.chosen.len() == self.selections.get() {
+            self.error = Some(format!("Can't select more than {}", self.selections));
+        } else {
+            self.chosen.push(current, ());
+        }
+    }
+
+    /// Add an option read from `line`, as "label\tvalue" or just "value",
+    /// or a section header
+    fn add(&mut self, line: String) {
+        if !self.keep_empty && line.trim().is_empty() {
+            return;
+        }
+        let header = self
+            .header_prefix
+            .as_ref()
+            .and_then(|prefix| line.strip_prefix(prefix.as_str()));
+        let (label, value) = match header {
+            Some(header) => (header, ""),
+            None => line.split_once('\t').unwrap_or((&line, &line)),
+        };
+        self.choices.push(label.to_owned());
+        self.values.push(value.to_owned());
+        self.headers.push(header.is_some());
+
+        let i = self.choices.len() - 1;
+        if self.shown(i) {
+            self.visible.push(i);
+        }
+    }
+
+    /// Whether choice `i` matches the query, hiding the headers while filtering
+    fn shown(&self, i: usize) -> bool {
+        let query = self.query.to_lowercase();
+        (query.is_empty() || !self.headers[i]) && self.choices[i].to_lowercase().contains(&query)
+    }
+
+    /// Narrow the visible choices to those containing the query
+    fn refilter(&mut self) {
+        self.visible = (0..self.choices.len()).filter(|i| self.shown(*i)).collect();
+        self.first();
+    }
+
+    /// Add the options that arrived on stdin, returning whether there were any
+    fn poll_feed(&mut self) -> bool {
+        let Some(feed) = &self.feed else {
+            return false;
+        };
+        let lines: Vec<String> = feed.try_iter().collect();
+        let received = !lines.is_empty();
+        for line in lines {
+            self.add(line);
+        }
+        // the cursor may still be on a header, or nothing at all
+        if received && !self.visible.is_empty() && !self.selectable(self.cursor_loc) {
+            self.first();
+        }
+        received
+    }
+
+    /// Whether the row at `loc` in `visible` can hold the cursor
+    fn selectable(&self, loc: usize) -> bool {
+        !self.headers[self.visible[loc]]
+    }
+
+    /// Move the cursor to the next selectable row, returning whether it moved
+    fn move_cursor(&mut self, down: bool) -> bool {
+        let next = if down {
+            (self.cursor_loc + 1..self.visible.len()).find(|loc| self.selectable(*loc))
+        } else {
+            (0..self.cursor_loc).rev().find(|loc| self.selectable(*loc))
+        };
+        if let Some(loc) = next {
+            self.cursor_loc = loc;
+        }
+        next.is_some()
+    }
+
+    /// Move the cursor a page of `height` rows, returning whether it moved
+    fn page(&mut self, down: bool, height: usize) -> bool {
+        if self.visible.is_empty() {
+            return false;
+        }
+        let before = self.cursor_loc;
+        self.cursor_loc = if down {
+            (before + height).min(self.visible.len() - 1)
+        } else {
+            before.saturating_sub(height)
+        };
+        // land on an option rather than a header
+        if !self.selectable(self.cursor_loc) && !self.move_cursor(down) && !self.move_cursor(!down)
+        {
+            self.cursor_loc = before;
+        }
+        self.cursor_loc != before
+    }
+
+    /// Move the cursor to the first selectable row, returning whether it moved
+    fn first(&mut self) -> bool {
+        let first = (0..self.visible.len())
+            .find(|loc| self.selectable(*loc))
+            .unwrap_or(0);
+        let changed = first != self.cursor_loc;
+        self.cursor_loc = first;
+        changed
+    }
+
+    /// Move the cursor to the last selectable row, returning whether it moved
+    fn last(&mut self) -> bool {
+        let last = (0..self.visible.len())
+            .rev()
+            .find(|loc| self.selectable(*loc))
+            .unwrap_or(0);
+        let changed = last != self.cursor_loc;
+        self.cursor_loc = last;
+        changed
+    }
+
+    /// Rows taken by the title, hint, filter, options and the overflow indicator
+    fn content_height(&self, filter: bool, height: Option<usize>) -> usize {
+        let shown = height.map_or(self.visible.len(), |height| height.min(self.visible.len()));
+        let overflow = (shown < self.visible.len()) as usize;
+        3 + filter as usize + shown + overflow
+    }
+
+    /// Keep the cursor within a viewport of `height` rows
+    fn scroll(&mut self, height: Option<usize>) {
+        let Some(height) = height else { return };
+        if self.cursor_loc < self.offset {
+            self.offset = self.cursor_loc;
+        } else if self.cursor_loc >= self.offset + height {
+            self.offset = self.cursor_loc + 1 - height;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TyperState {
+    /// Text printed so far, a prefix of the text
+    typed: String,
+    done_printing: bool,
+    last_updated: Instant,
+    cursor_visible: bool,
+    last_blink: Instant,
+}
+
+enum Component {
+    Text {
+        width: usize,
+        placeholder: String,
+        prefix: String,
+        style: Style,
+        mask: Option<char>,
+        validate: Option<Regex>,
+        validate_message: String,
+        cursor_style: CursorStyle,
+        cursor_blink: bool,
+        history_file: Option<PathBuf>,
+        /// Words to complete with Tab
+        completions: Vec<String>,
+        show_count: bool,
+        submit_on: Key,
+        suggestion: Option<String>,
+        state: TextState,
+    },
+    Textarea {
+        width: usize,
+        height: usize,
+        placeholder: String,
+        submit_key: char,
+        state: TextareaState,
+    },
+    Confirm {
+        text: String,
+        width: usize,
+        style: Style,
+        theme: Theme,
+        height: Option<usize>,
+        /// Button labels, no and yes unless given as options
+        labels: Vec<String>,
+        /// Labels centered in their buttons
+        buttons: Vec<String>,
+        /// Whether this is a yes or no question, answered by the exit code
+        binary: bool,
+        print: bool,
+        timeout: Option<Duration>,
+        /// Text to type before yes is enabled
+        require_text: Option<String>,
+        instant: bool,
+        state: ConfirmState,
+    },
+    Spinner {
+        speed: Duration,
+        text: String,
+        title: Option<String>,
+        align: SpinnerAlign,
+        width: usize,
+        /// Number of output lines to show
+        output_height: usize,
+        print_output: bool,
+        timeout: Option<Duration>,
+        show_time: bool,
+        until: Option<Regex>,
+        success_message: Option<String>,
+        error_message: Option<String>,
+        /// Shell command whose output replaces `text`
+        on_tick: Option<String>,
+        state: SpinnerState,
+    },
+    Typer {
+        speed: Duration,
+        wait: Duration,
+        width: usize,
+        text: String,
+        looping: bool,
+        color: Option<Color>,
+        bold: bool,
+        state: TyperState,
+    },
+    Choose {
+        text: String,
+        style: Style,
+        theme: Theme,
+        filter: bool,
+        ordered: bool,
+        output_delimiter: String,
+        print_index: bool,
+        height: Option<usize>,
+        strict: bool,
+        mouse: bool,
+        /// Columns for each option, longer labels are truncated
+        width: usize,
+        cursor_prefix: String,
+        empty_code: Option<u8>,
+        selected_string: String,
+        unselected_string: String,
+        state: ChooseState,
+    },
+    Filter {
+        prefix: String,
+        theme: Theme,
+        height: usize,
+        state: FilterState,
+    },
+    Pager {
+        width: usize,
+        height: usize,
+        state: PagerState,
+    },
+    Progress {
+        width: usize,
+        theme: Theme,
+        state: ProgressState,
+    },
+    Menu {
+        text: String,
+        style: Style,
+        theme: Theme,
+        height: usize,
+        state: MenuState,
+    },
+    Timer {
+        text: String,
+        style: Style,
+        state: TimerState,
+    },
+    Stopwatch {
+        text: String,
+        style: Style,
+        state: StopwatchState,
+    },
+    Slider {
+        text: String,
+        width: usize,
+        style: Style,
+        theme: Theme,
+        state: SliderState,
+    },
+    Pause {
+        width: usize,
+        style: Style,
+        state: PauseState,
+    },
+}
+
+impl Component {
+    /// Create the component, or return an error message
+    pub fn from_opts(opts: &Opts) -> Result<Component, String> {
+        Ok(match &opts.subcommand {
+            Subcommand::Text {
+                placeholder,
+                prefix,
+                password,
+                mask,
+                char_limit,
+                value,
+                validate,
+                validate_message,
+                cursor_style,
+                cursor_blink,
+                history,
+                complete,
+                show_count,
+                submit_on,
+                suggestion,
+            } => {
+                if *password && history.is_some() {
+                    return Err("--history can't be used with --password".to_owned());
+                }
+                if *password && suggestion.is_some() {
+                    return Err("--suggestion can't be used with --password".to_owned());
+                }
+                let entries: Vec<String> = match history {
+                    Some(path) => match fs::read_to_string(path) {
+                        Ok(contents) => contents.lines().map(ToOwned::to_owned).collect(),
+                        Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+                        Err(e) => return Err(format!("{}: {e}", path.display())),
+                    },
+                    None => vec![],
+                };
+                let completions = match complete {
+                    Some(path) if path.as_os_str() == "-" => read_stdin_lines(false)?,
+                    Some(path) => fs::read_to_string(path)
+                        .map_err(|e| format!("{}: {e}", path.display()))?
+                        .lines()
+                        .map(ToOwned::to_owned)
+                        .collect(),
+                    None => vec![],
+                };
+
+                let mut state = TextState {
+                    input: value.clone(),
+                    cursor: value.graphemes(true).count(),
+                    offset: 0,
+                    error: None,
+                    history_index: entries.len(),
+                    history: entries,
+                    draft: String::new(),
+                    completion: None,
+                    revealed: false,
+                    char_limit: *char_limit,
+                };
+                // the initial value is held to the limit like typed text
+                state.limit();
+
+                Component::Text {
+                    width: opts.width,
+                    placeholder: placeholder.clone(),
+                    prefix: prefix.clone(),
+                    style: opts.style.clone().unwrap_or_default(),
+                    mask: password.then_some(*mask),
+                    validate: validate.clone(),
+                    validate_message: validate_message.clone(),
+                    cursor_style: *cursor_style,
+                    cursor_blink: *cursor_blink,
+                    history_file: history.clone(),
+                    completions,
+                    show_count: *show_count,
+                    submit_on: *submit_on,
+                    suggestion: suggestion.clone(),
+                    state,
+                }
+            }
+            Subcommand::Textarea {
+                placeholder,
+                submit_key,
+            } => Component::Textarea {
+                width: opts.width,
+                height: opts.height.unwrap_or(5),
+                placeholder: placeholder.clone(),
+                submit_key: *submit_key,
+                state: TextareaState::default(),
+            },
+            Subcommand::Confirm {
+                text,
+                no,
+                yes,
+                default_yes,
+                timeout,
+                print,
+                options,
+                require_text,
+                instant,
+            } => {
+                let binary = options.is_empty();
+                let default_yes = match env::var("RUM_CONFIRM_DEFAULT") {
+                    Ok(default) if !default_yes && binary && require_text.is_none() => {
+                        match default.to_lowercase().as_str() {
+                            "yes" | "y" => true,
+                            "no" | "n" | "" => false,
+                            _ => {
+                                return Err(format!(
+                                    "RUM_CONFIRM_DEFAULT: expected yes or no, got '{default}'"
+                                ))
+                            }
+                        }
+                    }
+                    _ => *default_yes,
+                };
+                let labels = if binary {
+                    vec![no.clone(), yes.clone()]
+                } else {
+                    options.clone()
+                };
+                let buttons = labels
+                    .iter()
+                    .map(|label| format!("{: ^10}", label))
+                    .collect();
+
+                Component::Confirm {
+                    text: text.clone(),
+                    width: opts.width,
+                    style: opts.style.clone().unwrap_or_default(),
+                    theme: opts.theme.into(),
+                    height: opts.height,
+                    labels,
+                    buttons,
+                    binary,
+                    print: *print,
+                    timeout: timeout.map(Duration::from_secs),
+                    require_text: require_text.clone(),
+                    instant: *instant,
+                    state: ConfirmState {
+                        selected: default_yes as usize,
+                        started: Instant::now(),
+                        countdown: timeout.unwrap_or(0),
+                        typed: String::new(),
+                    },
+                }
+            }
+            Subcommand::Spinner {
+                text,
+                title,
+                speed,
+                command,
+                spinner_style,
+                frames,
+                align,
+                show_output,
+                print_output,
+                timeout,
+                show_time,
+                success_message,
+                error_message,
+                until,
+                shell,
+                status_fd,
+                then,
+                capture_stderr,
+                on_tick,
+            } => {
+                let chars: Vec<String> = match spinner_style {
+                    SpinnerStyle::Braille => vec!["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"],
+                    SpinnerStyle::VBar => vec![
+                        "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█", "▇", "▆", "▅", "▄", "▃", "▂", "▁",
+                    ],
+                    SpinnerStyle::Arrow => vec!["←", "↖", "↑", "↗", "→", "↘", "↓", "↙"],
+                    SpinnerStyle::Circle => vec!["◜", "◠", "◝", "◞", "◡", "◟"],
+                    SpinnerStyle::Pulse => vec!["█", "▓", "▒", "░"],
+                    SpinnerStyle::Line => vec!["|", "/", "-", "\\"],
+                    SpinnerStyle::Moon => vec![
+                        "\u{1f311}",
+                        "\u{1f312}",
+                        "\u{1f313}",
+                        "\u{1f314}",
+                        "\u{1f315}",
+                        "\u{1f316}",
+                        "\u{1f317}",
+                        "\u{1f318}",
+                    ],
+                    SpinnerStyle::Monkey => vec!["\u{1f648}", "\u{1f649}", "\u{1f64a}"],
+                    SpinnerStyle::Meter => vec!["▱▱▱", "▰▱▱", "▰▰▱", "▰▰▰", "▰▰▱", "▰▱▱", "▱▱▱"],
+                    SpinnerStyle::Points => vec!["∙∙∙", "●∙∙", "∙●∙", "∙∙●"],
+                    SpinnerStyle::Progress => vec![
+                        "[     ]", "[>    ]", "[=>   ]", "[==>  ]", "[===> ]", "[====>]", "[=====]",
+                    ],
+                }
+                .iter()
+                .map(|e| e.to_string())
+                .collect();
+                let chars = match frames {
+                    Some(frames) => frames.split(',').map(ToOwned::to_owned).collect(),
+                    None => chars,
+                };
+
+                if then.iter().any(|step| step.trim().is_empty()) {
+                    return Err("Got an empty --then command".to_owned());
+                }
+                // with --shell, each --then is a script of its own, quoting and all
+                let mut steps: VecDeque<Command> =
+                    if *shell {
+                        std::iter::once(command.join(" "))
+                            .chain(then.iter().cloned())
+                            .map(|script| {
+                                let mut sh = Command::new("sh");
+                                sh.arg("-c").arg(script);
+                                sh
+                            })
+                            .collect()
+                    } else {
+                        std::iter::once(command.clone())
+                            .chain(then.iter().map(|step| {
+                                step.split_whitespace().map(ToOwned::to_owned).collect()
+                            }))
+                            .map(|command: Vec<String>| {
+                                let mut direct = Command::new(&command[0]);
+                                direct.args(&command[1..]);
+                                direct
+                            })
+                            .collect()
+                    };
+                let total = steps.len();
+                let piped = *show_output || *print_output || until.is_some();
+                let mut child = match spawn(&mut steps[0], piped, *capture_stderr) {
+                    Ok(child) => child,
+                    // 127 like a shell's "command not found", and like a --then that fails to start
+                    Err(message) => {
+                        eprintln!("{message}");
+                        std::process::exit(127);
+                    }
+                };
+                steps.pop_front();
+                let output = child.stdout.take().map(read_lines);
+                let errors = child.stderr.take().map(read_lines);
+                let status = status_fd.map(status_file).transpose()?;
+                let mut state = SpinnerState {
+                    chars: chars.to_owned(),
+                    last_updated: Instant::now(),
+                    started: Instant::now(),
+                    timed_out: false,
+                    matched: false,
+                    progress: 0,
+                    child,
+                    output,
+                    lines: vec![],
+                    status,
+                    steps,
+                    step: 1,
+                    total,
+                    piped,
+                    capture_stderr: *capture_stderr,
+                    errors,
+                    spawn_error: None,
+                    probe: None,
+                };
+                state.report("started", None);
+                Component::Spinner {
+                    text: text.clone(),
+                    title: title.clone(),
+                    align: *align,
+                    width: opts.width,
+                    output_height: if *show_output {
+                        opts.height.unwrap_or(5)
+                    } else {
+                        0
+                    },
+                    print_output: *print_output,
+                    timeout: timeout.map(Duration::from_secs),
+                    show_time: *show_time,
+                    until: until.clone(),
+                    success_message: success_message.clone(),
+                    error_message: error_message.clone(),
+                    on_tick: on_tick.clone(),
+                    state,
+                    speed: Duration::from_millis(*speed as u64),
+                }
+            }
+            Subcommand::Typer {
+                speed,
+                text,
+                wait,
+                looping,
+                color,
+                bold,
+            } => Component::Typer {
+                speed: Duration::from_millis(*speed as u64),
+                wait: Duration::from_millis(*wait as u64),
+                width: opts.width,
+                text: match text {
+                    Some(text) => text.clone(),
+                    None if stdin().is_terminal() => {
+                        return Err("Give the text with --text or on stdin".to_owned())
+                    }
+                    None => read_stdin_lines(false)?.join("\n"),
+                },
+                looping: *looping,
+                color: *color,
+                bold: *bold,
+                state: TyperState {
+                    typed: String::new(),
                     last_updated: Instant::now(),
                     done_printing: false,
+                    cursor_visible: true,
+                    last_blink: Instant::now(),
                 },
             },
             Subcommand::Choose {
                 selections,
                 text,
                 inexact,
+                min,
+                max,
+                filter,
+                ordered,
+                output_delimiter,
+                read0,
+                print_index,
+                header_prefix,
+                options_file,
+                initial_index,
+                selected,
+                strict,
+                mouse,
+                cursor_prefix,
+                empty_code,
+                keep_empty,
+                values_from,
+                live,
+            } => {
+                let selections = max.unwrap_or(*selections);
+                let min = min.unwrap_or(if *inexact { 0 } else { selections.get() });
+                if min > selections.get() {
+                    return Err("Minimum selections exceed the maximum".to_owned());
+                }
+
+                let mut state = ChooseState {
+                    visible: vec![],
+                    choices: vec![],
+                    values: vec![],
+                    headers: vec![],
+                    chosen: LruCache::new(selections),
+                    cursor_loc: 0,
+                    offset: 0,
+                    selections,
+                    min,
+                    query: String::new(),
+                    error: None,
+                    list_top: 0,
+                    header_prefix: header_prefix.clone(),
+                    keep_empty: *keep_empty,
+                    feed: None,
+                };
+
+                // Grab all options from stdin, or follow it as it is written to
+                if *live {
+                    state.feed = Some(read_lines(stdin()));
+                } else {
+                    for line in read_options(options_file.as_ref(), *read0)? {
+                        state.add(line);
+                    }
+                    if state.headers.iter().all(|header| *header) {
+                        return Err("Got 0 choices!".to_owned());
+                    }
+                }
+                if let Some(path) = values_from {
+                    let options: Vec<usize> = state.options().collect();
+                    let mapped = read_options(Some(path), *read0)?;
+                    if mapped.len() != options.len() {
+                        return Err(format!(
+                            "{}: got {} values for {} options",
+                            path.display(),
+                            mapped.len(),
+                            options.len()
+                        ));
+                    }
+                    for (i, value) in options.into_iter().zip(mapped) {
+                        state.values[i] = value;
+                    }
+                }
+
+                // without a height, fit below the padding, border, title, hint,
+                // filter and the overflow indicator
+                let height = opts.height.or_else(|| {
+                    let (_, rows) = size().ok()?;
+                    let border = 2 * opts.border.inset() as usize;
+                    let chrome = opts.padding as usize + border + 4 + *filter as usize;
+                    let available = (rows as usize).saturating_sub(chrome);
+                    (*live || available < state.choices.len()).then_some(available.max(1))
+                });
+
+                // place the cursor on the first option rather than a header
+                state.refilter();
+                let options: Vec<usize> = state.options().collect();
+                if let Some(index) = initial_index {
+                    if let Some(&choice) = options.get(*index).or(options.last()) {
+                        // nothing is filtered yet, so every choice is visible in place
+                        state.cursor_loc = choice;
+                        state.scroll(height);
+                    }
+                }
+
+                for &i in selected {
+                    let Some(&choice) = options.get(i) else {
+                        return Err(format!("Can't select option {i}"));
+                    };
+                    // pushing past the capacity would drop earlier selections
+                    if state.chosen.len() == state.selections.get() {
+                        break;
+                    }
+                    state.chosen.push(choice, ());
+                }
+
+                let (selected_string, unselected_string) = if selections.get() == 1 {
+                    ("(x) ".to_owned(), "( ) ".to_owned())
+                } else {
+                    ("[x] ".to_owned(), "[ ] ".to_owned())
+                };
+                Component::Choose {
+                    text: text.clone(),
+                    style: opts.style.clone().unwrap_or_default(),
+                    theme: opts.theme.into(),
+                    filter: *filter,
+                    ordered: *ordered,
+                    output_delimiter: output_delimiter.clone(),
+                    print_index: *print_index,
+                    height,
+                    strict: *strict,
+                    mouse: *mouse,
+                    width: opts.width,
+                    cursor_prefix: cursor_prefix.clone(),
+                    empty_code: *empty_code,
+                    state,
+                    selected_string,
+                    unselected_string,
+                }
+            }
+            Subcommand::Filter {
+                prefix,
+                read0,
+                options_file,
+            } => {
+                let choices = read_options(options_file.as_ref(), *read0)?;
+                if choices.is_empty() {
+                    return Err("Got 0 choices!".to_owned());
+                }
+
+                Component::Filter {
+                    prefix: prefix.clone(),
+                    theme: opts.theme.into(),
+                    height: opts.height.unwrap_or(10),
+                    state: FilterState::new(choices),
+                }
+            }
+            Subcommand::Pager => Component::Pager {
+                width: opts.width,
+                height: opts.height.unwrap_or(20),
+                state: PagerState::new(read_stdin_lines(false)?),
+            },
+            Subcommand::Progress => Component::Progress {
+                width: opts.width,
+                theme: opts.theme.into(),
+                state: ProgressState::new(read_lines(stdin())),
+            },
+            Subcommand::Menu { text } => {
+                let state = MenuState::new(read_stdin_lines(false)?);
+                if state.items.is_empty() {
+                    return Err("Got 0 choices!".to_owned());
+                }
+
+                Component::Menu {
+                    text: text.clone(),
+                    style: opts.style.clone().unwrap_or_default(),
+                    theme: opts.theme.into(),
+                    height: opts.height.unwrap_or(10),
+                    state,
+                }
+            }
+            Subcommand::Timer { duration, text } => Component::Timer {
+                text: text.clone(),
+                style: opts.style.clone().unwrap_or_default(),
+                state: TimerState::new(Duration::from_secs(*duration)),
+            },
+            Subcommand::Slider {
+                text,
+                min,
+                max,
+                step,
+                value,
+            } => Component::Slider {
+                text: text.clone(),
+                width: opts.width,
+                style: opts.style.clone().unwrap_or_default(),
+                theme: opts.theme.into(),
+                state: SliderState::new(*min, *max, *step, value.unwrap_or(*min))?,
+            },
+            Subcommand::Stopwatch { text } => Component::Stopwatch {
+                text: text.clone(),
+                style: opts.style.clone().unwrap_or_default(),
+                state: StopwatchState::new(),
+            },
+            Subcommand::Pause { text } => Component::Pause {
+                width: opts.width,
+                style: opts.style.clone().unwrap_or_default(),
+                state: PauseState::new(text, opts.width),
+            },
+        })
+    }
+
+    /// Return the stdout and return code of the component
+    pub fn result(self) -> Result<(String, u8), ()> {
+        match self {
+            Component::Text {
+                history_file,
+                state: TextState { input, .. },
+                ..
+            } => {
+                if let Some(path) = history_file.filter(|_| !input.is_empty()) {
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .and_then(|mut file| writeln!(file, "{input}"))
+                        .ok(); // swallow error
+                }
+                Ok((input, 0))
+            }
+            Component::Textarea { state, .. } => Ok((state.result(), 0)),
+            Component::Confirm {
+                mut labels,
+                binary,
+                print,
+                state: ConfirmState { selected, .. },
+                ..
+            } => {
+                let answer = labels.swap_remove(selected);
+                if !binary {
+                    return Ok((answer, 0));
+                }
+                // yes is the second button
+                let code = if selected == 1 { 0 } else { 1 };
+                Ok((if print { answer } else { String::new() }, code))
+            }
+            Component::Spinner {
+                print_output,
+                success_message,
+                error_message,
+                mut state,
+                ..
+            } => {
+                let SpinnerState {
+                    child,
+                    output,
+                    lines,
+                    errors,
+                    timed_out,
+                    matched,
+                    spawn_error,
+                    ..
+                } = &mut state;
+                let (output, code) = if *timed_out {
+                    ("".to_owned(), 124)
+                } else if *matched {
+                    // The child was killed on purpose, so its status does not matter
+                    let output = if print_output {
+                        lines.join("\n")
+                    } else {
+                        String::new()
+                    };
+                    (output, 0)
+                } else if let Some(message) = spawn_error {
+                    eprintln!("{message}");
+                    ("".to_owned(), 127)
+                } else if let Some(code) = child.try_wait().drop_error()? {
+                    // Assume that child is already finished
+                    let code = code.code().unwrap_or(1) as u8;
+                    if print_output && code == 0 {
+                        // Read the rest of the output
+                        lines.extend(output.iter().flatten());
+                        (lines.join("\n"), code)
+                    } else if code != 0 && errors.is_some() {
+                        // why the failing command failed
+                        let errors: Vec<String> = errors.iter().flatten().collect();
+                        (errors.join("\n"), code)
+                    } else {
+                        ("".to_owned(), code)
+                    }
+                } else {
+                    child.kill().ok(); // swallow error
+                    ("".to_owned(), 1)
+                };
+
+                // leave a status line behind once the spinner is gone
+                let status = match code {
+                    0 => success_message.map(|message| (Color::Green, '✓', message)),
+                    _ => error_message.map(|message| (Color::Red, '✗', message)),
+                };
+                if let Some((color, mark, message)) = status {
+                    execute!(
+                        stderr(),
+                        SetForegroundColor(color),
+                        Print(mark),
+                        ResetColor,
+                        Print(format!(" {message}\n"))
+                    )
+                    .drop_error()?;
+                }
+
+                state.report("exited", Some(code));
+                Ok((output, code))
+            }
+            Component::Typer { .. } => Ok((String::new(), 0)),
+            Component::Choose {
+                ordered,
+                output_delimiter,
+                print_index,
+                empty_code,
+                state,
+                ..
+            } => {
+                let selected = state.selected(ordered, print_index);
+                let code = match empty_code {
+                    Some(code) if selected.is_empty() => code,
+                    _ => 0,
+                };
+                Ok((selected.join(&output_delimiter), code))
+            }
+            Component::Filter { state, .. } => match state.current() {
+                Some(i) => Ok((state.choices[i].clone(), 0)),
+                None => Ok((String::new(), 1)),
+            },
+            Component::Pager { .. } => Ok((String::new(), 0)),
+            Component::Progress { .. } => Ok((String::new(), 0)),
+            Component::Menu { mut state, .. } => {
+                Ok((state.items.swap_remove(state.cursor_loc).label, 0))
+            }
+            Component::Timer { state, .. } => Ok((String::new(), state.skipped as u8)),
+            Component::Stopwatch { state, .. } => {
+                Ok((format_duration(state.stopped.unwrap_or_default()), 0))
+            }
+            Component::Slider { state, .. } => Ok((state.value(), 0)),
+            Component::Pause { .. } => Ok((String::new(), 0)),
+        }
+    }
+
+    /// Stop whatever the component started, when it is left without a result
+    pub fn abort(&mut self) {
+        if let Component::Spinner { state, .. } = self {
+            state.child.kill().ok(); // swallow error
+            state.child.wait().ok(); // swallow error
+            state.report("exited", None);
+        }
+    }
+
+    /// Settle on the default result without any input, for --non-interactive
+    pub fn settle(&mut self) {
+        match self {
+            Component::Spinner { state, .. } => {
+                // nothing to skip to, so run every command
+                while let Ok(status) = state.child.wait() {
+                    if !state.advance(status.success()) {
+                        break;
+                    }
+                }
+            }
+            Component::Choose { state, .. } if state.chosen.is_empty() => {
+                if let Some(current) = state.current() {
+                    state.chosen.put(current, ());
+                }
+            }
+            Component::Stopwatch { state, .. } => state.stop(),
+            _ => (),
+        }
+    }
+
+    pub fn tick(&mut self, screen: &mut Screen) -> Result<bool, ()> {
+        let should_redraw: bool = match self {
+            Component::Spinner {
+                state,
+                speed,
+                text,
+                timeout,
+                until,
+                on_tick,
+                ..
+            } => {
+                if let Some(status) = state.child.try_wait().drop_error()? {
+                    if !state.advance(status.success()) {
+                        return Ok(true);
+                    }
+                }
+
+                if timeout.is_some_and(|timeout| state.started.elapsed() > timeout) {
+                    state.child.kill().ok(); // swallow error
+                    state.timed_out = true;
+                    return Ok(true);
+                }
+
+                // Collect new output
+                let before = state.lines.len();
+                if let Some(output) = &state.output {
+                    state.lines.extend(output.try_iter());
+                }
+                let received = state.lines.len() - before;
+
+                if let Some(until) = until {
+                    if state.lines[before..]
+                        .iter()
+                        .any(|line| until.is_match(line))
+                    {
+                        state.child.kill().ok(); // swallow error
+                        state.matched = true;
+                        return Ok(true);
+                    }
+                }
+
+                // pick up the --on-tick output once it is ready
+                let probed = match state.probe.as_ref().map(Receiver::try_recv) {
+                    Some(Ok(output)) => {
+                        *text = output;
+                        state.probe = None;
+                        true
+                    }
+                    Some(Err(TryRecvError::Disconnected)) => {
+                        state.probe = None;
+                        false
+                    }
+                    _ => false,
+                };
+
+                if state.last_updated.elapsed() > *speed {
+                    if let Some(command) = on_tick.as_ref().filter(|_| state.probe.is_none()) {
+                        state.probe = Some(probe(command));
+                    }
+                    // Update progress
+                    state.progress = (state.progress + 1) % state.chars.len();
+                    state.last_updated = Instant::now();
+                    state.report("frame", None);
+                    true
+                } else {
+                    received > 0 || probed
+                }
+            }
+            Component::Typer {
+                state,
+                speed,
+                wait,
+                text,
+                looping,
+                ..
             } => {
-                // Grab all options from stdin
-                let mut choices: Vec<String> = vec![];
-                for line in stdin().lines() {
-                    choices.push(line.unwrap());
+                if state.done_printing {
+                    if state.last_updated.elapsed() <= *wait {
+                        false
+                    } else if *looping {
+                        // Start over on a clear screen
+                        state.typed.clear();
+                        state.done_printing = false;
+                        state.last_updated = Instant::now();
+                        true
+                    } else {
+                        return Ok(true);
+                    }
+                } else {
+                    let blinked = state.last_blink.elapsed() > CURSOR_BLINK;
+                    if blinked {
+                        state.cursor_visible = !state.cursor_visible;
+                        state.last_blink = Instant::now();
+                    }
+
+                    if state.last_updated.elapsed() > *speed {
+                        let c = text[state.typed.len()..].graphemes(true).next();
+                        if let Some(c) = c {
+                            state.typed.push_str(c);
+                        } else {
+                            state.done_printing = true;
+                        }
+                        state.last_updated = Instant::now();
+                        true
+                    } else {
+                        blinked
+                    }
                 }
-                if choices.is_empty() {
-                    panic!("Got 0 choices!");
+            }
+            Component::Confirm {
+                timeout: Some(timeout),
+                state,
+                ..
+            } => {
+                let elapsed = state.started.elapsed();
+                if elapsed > *timeout {
+                    return Ok(true);
                 }
 
-                let (selected_string, unselected_string) = if selections.get() == 1 {
-                    ("(x) ".to_owned(), "( ) ".to_owned())
+                // Update countdown
+                let countdown = (*timeout - elapsed).as_secs() + 1;
+                if countdown != state.countdown {
+                    state.countdown = countdown;
+                    true
                 } else {
-                    ("[x] ".to_owned(), "[ ] ".to_owned())
-                };
-                Component::Choose {
-                    text: text.clone(),
-                    state: ChooseState {
-                        choices,
-                        chosen: LruCache::new(*selections),
-                        cursor_loc: 0,
-                        selections: *selections,
-                    },
-                    inexact: *inexact,
-                    selected_string,
-                    unselected_string,
+                    false
+                }
+            }
+            Component::Progress { state, .. } => {
+                let (changed, finished) = state.poll();
+                if finished {
+                    return Ok(true);
+                }
+                changed
+            }
+            Component::Timer { state, .. } => {
+                let (changed, finished) = state.poll();
+                if finished {
+                    return Ok(true);
                 }
+                changed
             }
+            Component::Stopwatch { state, .. } => state.poll(),
+            Component::Choose { state, .. } => state.poll_feed(),
+            _ => false,
+        };
+
+        if should_redraw {
+            self.draw(screen)?;
         }
+
+        Ok(false)
     }
 
-    /// Return the stdout and return code of the component
-    pub fn result(self) -> Result<(String, u8), ()> {
+    /// Columns and rows taken up by the content, for sizing the border
+    fn extent(&self) -> (usize, usize) {
+        let len = |s: &str| s.graphemes(true).count();
         match self {
             Component::Text {
-                state: TextState { input },
+                width,
+                prefix,
+                show_count,
+                state,
                 ..
-            } => Ok((input, 0)),
+            } => {
+                // room for the cursor past the end, and the count
+                let count = state.input.graphemes(true).count();
+                let count = if *show_count {
+                    len(&format!(" {count} chars"))
+                } else {
+                    0
+                };
+                let rows = if state.error.is_some() { 3 } else { 1 };
+                (len(prefix) + width + 1 + count, rows)
+            }
+            Component::Textarea { width, height, .. } => (width + 1, height + 2),
             Component::Confirm {
-                state: ConfirmState { confirmed },
+                text,
+                width,
+                height,
+                buttons,
+                timeout,
+                require_text,
+                state,
                 ..
-            } => Ok((String::new(), if confirmed { 0 } else { 1 })),
+            } => {
+                let input = require_text.as_ref().map_or(0, |required| {
+                    len(&format!("Type \"{required}\" to confirm: {} ", state.typed))
+                });
+                let buttons = buttons
+                    .iter()
+                    .map(|button| len(button) + 2)
+                    .sum::<usize>()
+                    .saturating_sub(2)
+                    + if timeout.is_some() { 5 } else { 0 };
+                let rows = wrap(text, *width).len() + 2 + require_text.is_some() as usize * 2;
+                (
+                    (*width).max(buttons).max(input),
+                    height.map_or(rows, |height| height.max(rows)),
+                )
+            }
             Component::Spinner {
-                state: SpinnerState { mut child, .. },
+                text,
+                title,
+                width,
+                output_height,
+                timeout,
+                show_time,
+                state,
                 ..
             } => {
-                // Assume that child is already finished
-                let output = child.try_wait().drop_error()?;
-                if let Some(code) = output {
-                    Ok(("".to_owned(), code.code().unwrap_or(1) as u8))
+                let frame = state.chars.iter().map(|c| len(c)).max().unwrap_or(0);
+                let step = if state.total > 1 {
+                    len(&format!(" ({}/{})", state.total, state.total))
                 } else {
-                    child.kill().ok(); // swallow error
-                    Ok(("".to_owned(), 1))
-                }
+                    0
+                };
+                let line = frame
+                    + 2
+                    + len(text)
+                    + step
+                    + if *show_time { 7 } else { 0 }
+                    + if timeout.is_some() { 5 } else { 0 };
+                let output = if *output_height > 0 {
+                    output_height + 1
+                } else {
+                    0
+                };
+                let title = title.as_deref().map_or(0, len);
+                ((*width).max(line).max(title), title.min(1) + 1 + output)
             }
-            Component::Typer { .. } => Ok((String::new(), 0)),
+            Component::Typer { width, text, .. } => (width + 1, wrap(text, *width).len()),
             Component::Choose {
-                state: ChooseState {
-                    choices, chosen, ..
-                },
+                text,
+                filter,
+                height,
+                width,
+                cursor_prefix,
+                selected_string,
+                state,
+                ..
+            } => {
+                let option = cursor_prefix.width() + selected_string.width() + 1;
+                let label_width = label_width(*width, cursor_prefix, selected_string);
+                let columns = state
+                    .visible
+                    .iter()
+                    .map(|i| option + state.choices[*i].width().min(label_width))
+                    .chain([len(text), len(&format!("/ {}", state.query)) + 1])
+                    .max()
+                    .unwrap_or(0);
+                let rows = state.content_height(*filter, *height);
+                (columns, height.map_or(rows, |height| height.max(rows)))
+            }
+            Component::Filter {
+                prefix,
+                height,
+                state,
                 ..
             } => {
-                let s = chosen
+                let columns = state
+                    .matches
                     .iter()
-                    .filter_map(|(k, _)| choices.get(*k).map(ToOwned::to_owned))
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                Ok((s, 0))
+                    .map(|i| len(&state.choices[*i]))
+                    .chain([len(prefix) + len(&state.query) + 1])
+                    .max()
+                    .unwrap_or(0);
+                (columns, 3 + height)
+            }
+            Component::Pager { width, height, .. } => (*width, height + 2),
+            Component::Progress { width, .. } => (width + 5, 1),
+            Component::Menu {
+                text,
+                height,
+                state,
+                ..
+            } => {
+                let columns = state
+                    .items
+                    .iter()
+                    .map(|item| len(&item.label))
+                    .chain([len(text)])
+                    .max()
+                    .unwrap_or(0);
+                (columns, 2 + state.items.len().min(*height))
+            }
+            Component::Timer { text, .. } => (len(text).max(len("Press any key to skip")), 3),
+            Component::Stopwatch { text, .. } => {
+                (len(text).max(len("Press Enter or Space to stop")), 3)
+            }
+            Component::Slider { width, state, .. } => (width + 1 + len(&state.value()), 3),
+            Component::Pause { width, state, .. } => (*width, state.lines.len()),
+        }
+    }
+
+    /// Interval the component needs to be ticked at to animate smoothly
+    pub fn frame_interval(&self) -> Option<Duration> {
+        match self {
+            Component::Spinner { speed, .. } | Component::Typer { speed, .. } => Some(*speed),
+            _ => None,
+        }
+    }
+
+    /// Whether plain characters are typed into the component
+    fn typing(&self) -> bool {
+        match self {
+            Component::Text { .. } | Component::Textarea { .. } | Component::Filter { .. } => true,
+            Component::Choose { filter, .. } => *filter,
+            Component::Confirm { require_text, .. } => require_text.is_some(),
+            _ => false,
+        }
+    }
+
+    /// The key that does `action` in this component
+    fn key_for(&self, action: Action) -> Key {
+        let code = match (action, self) {
+            (Action::Submit, Component::Text { submit_on, .. }) => return *submit_on,
+            (Action::Submit, Component::Textarea { submit_key, .. }) => {
+                return Key {
+                    code: KeyCode::Char(*submit_key),
+                    modifiers: KeyModifiers::CONTROL,
+                };
             }
+            (Action::Submit, Component::Pager { .. }) => KeyCode::Char('q'),
+            (Action::Submit, _) => KeyCode::Enter,
+            // space is typed into the filter
+            (Action::Toggle, Component::Choose { .. }) => KeyCode::Tab,
+            (Action::Toggle, _) => KeyCode::Char(' '),
+            (Action::Complete, _) => KeyCode::Tab,
+            (Action::Cancel, _) => KeyCode::Esc,
+            (Action::Up, _) => KeyCode::Up,
+            (Action::Down, _) => KeyCode::Down,
+            (Action::Left, _) => KeyCode::Left,
+            (Action::Right, _) => KeyCode::Right,
+            (Action::Home, _) => KeyCode::Home,
+            (Action::End, _) => KeyCode::End,
+            (Action::PageUp, _) => KeyCode::PageUp,
+            (Action::PageDown, _) => KeyCode::PageDown,
+        };
+        Key {
+            code,
+            modifiers: KeyModifiers::NONE,
         }
     }
 
-    pub fn tick(&mut self, screen: &mut Stderr) -> Result<bool, ()> {
+    /// Update the component with keystroke event
+    /// Returns Ok(true) if component is in the terminal state
+    /// # Errors if unable to draw to the terminal
+    pub fn update(&mut self, event: &Event, screen: &mut Screen) -> Result<bool, ()> {
         let should_redraw: bool = match self {
-            Component::Spinner { state, speed, .. } => {
-                if let Some(_) = state.child.try_wait().drop_error()? {
+            Component::Text {
+                mask,
+                validate,
+                validate_message,
+                completions,
+                submit_on,
+                suggestion,
+                state,
+                ..
+            } => {
+                // any key but Tab stops cycling through completions
+                let completing = state.completion.take();
+                match event {
+                    event if submit_on.matches(event) => match validate {
+                        Some(re) if !re.is_match(&state.input) => {
+                            state.error = Some(validate_message.clone());
+                            screen.ring()?;
+                            true
+                        }
+                        _ => return Ok(true),
+                    },
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Tab, ..
+                    }) => {
+                        state.accept(suggestion.as_deref())
+                            || state.complete(completions, completing)
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('r'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }) if mask.is_some() => {
+                        state.revealed = !state.revealed;
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('w'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }) => {
+                        // delete back to the start of the previous word
+                        let end = grapheme_byte_index(&state.input, state.cursor);
+                        let start = state.word_start();
+                        state.cursor -= state.input[start..end].graphemes(true).count();
+                        state.input.replace_range(start..end, "");
+                        start != end
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Left,
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    })
+                    | Event::Key(KeyEvent {
+                        code: KeyCode::Char('b'),
+                        modifiers: KeyModifiers::ALT,
+                        ..
+                    }) => state.jump(state.word_start()),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Right,
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    })
+                    | Event::Key(KeyEvent {
+                        code: KeyCode::Char('f'),
+                        modifiers: KeyModifiers::ALT,
+                        ..
+                    }) => state.jump(state.word_end()),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('u'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }) => {
+                        let end = grapheme_byte_index(&state.input, state.cursor);
+                        state.input.replace_range(..end, "");
+                        state.cursor = 0;
+                        end > 0
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('k'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }) => {
+                        let start = grapheme_byte_index(&state.input, state.cursor);
+                        let changed = start < state.input.len();
+                        state.input.truncate(start);
+                        changed
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char(c),
+                        ..
+                    }) if state.char_limit == 0
+                        || state.input.graphemes(true).count() < state.char_limit =>
+                    {
+                        let i = grapheme_byte_index(&state.input, state.cursor);
+                        state.input.insert(i, *c);
+                        state.cursor = (state.cursor + 1).min(state.input.graphemes(true).count());
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Backspace,
+                        ..
+                    }) if state.cursor > 0 => {
+                        let start = grapheme_byte_index(&state.input, state.cursor - 1);
+                        let end = grapheme_byte_index(&state.input, state.cursor);
+                        state.input.replace_range(start..end, "");
+                        state.cursor -= 1;
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Left | KeyCode::Home,
+                        ..
+                    }) if state.cursor == 0 => false,
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Right | KeyCode::End,
+                        ..
+                    }) if state.cursor == state.input.graphemes(true).count() => {
+                        state.accept(suggestion.as_deref())
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Left,
+                        ..
+                    }) => {
+                        state.cursor -= 1;
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Right,
+                        ..
+                    }) => {
+                        state.cursor += 1;
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Home,
+                        ..
+                    }) => {
+                        state.cursor = 0;
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::End, ..
+                    }) => {
+                        state.cursor = state.input.graphemes(true).count();
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Up, ..
+                    }) if state.history_index > 0 => {
+                        state.recall(state.history_index - 1);
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Down,
+                        ..
+                    }) if state.history_index < state.history.len() => {
+                        state.recall(state.history_index + 1);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            Component::Textarea {
+                submit_key, state, ..
+            } => match event {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char(c),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) if c == submit_key => return Ok(true),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char(c),
+                    ..
+                }) => {
+                    state.insert(*c);
+                    true
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                }) => {
+                    state.newline();
+                    true
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Backspace,
+                    ..
+                }) => state.backspace(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Left,
+                    ..
+                }) => state.left(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Right,
+                    ..
+                }) => state.right(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Up, ..
+                }) => state.up(),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Down,
+                    ..
+                }) => state.down(),
+                _ => false,
+            },
+            Component::Confirm {
+                labels,
+                require_text: Some(required),
+                instant,
+                state,
+                ..
+            } => match event {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char(c),
+                    modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+                    ..
+                }) => {
+                    state.typed.push(*c);
+                    // yes is disabled again once the text stops matching
+                    if state.typed != *required {
+                        state.selected = 0;
+                    }
+                    true
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Backspace,
+                    ..
+                }) => {
+                    state.typed.pop();
+                    if state.typed != *required {
+                        state.selected = 0;
+                    }
+                    true
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Right,
+                    ..
+                }) if state.selected + 1 < labels.len() && state.typed == *required => {
+                    state.selected += 1;
+                    if *instant {
+                        return Ok(true);
+                    }
+                    true
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Left,
+                    ..
+                }) if state.selected > 0 => {
+                    state.selected -= 1;
+                    if *instant {
+                        return Ok(true);
+                    }
+                    true
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Enter,
+                    modifiers: KeyModifiers::NONE,
+                    ..
+                }) => return Ok(true),
+                _ => false,
+            },
+            Component::Confirm {
+                labels,
+                binary,
+                instant,
+                state,
+                ..
+            } => match event {
+                // pick the neighbouring option, or keep the last one, and submit
+                Event::Key(KeyEvent {
+                    code: KeyCode::Right | KeyCode::Char('l'),
+                    ..
+                }) if *instant => {
+                    state.selected = (state.selected + 1).min(labels.len() - 1);
                     return Ok(true);
                 }
-
-                if state.last_updated.elapsed() > *speed {
-                    // Update progress
-                    state.progress = (state.progress + 1) % state.chars.len();
-                    state.last_updated = Instant::now();
+                Event::Key(KeyEvent {
+                    code: KeyCode::Left | KeyCode::Char('h'),
+                    ..
+                }) if *instant => {
+                    state.selected = state.selected.saturating_sub(1);
+                    return Ok(true);
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Right | KeyCode::Char('l'),
+                    ..
+                }) if state.selected + 1 < labels.len() => {
+                    state.selected += 1;
                     true
-                } else {
-                    false
                 }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Left | KeyCode::Char('h'),
+                    ..
+                }) if state.selected > 0 => {
+                    state.selected -= 1;
+                    true
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('y' | 'Y'),
+                    ..
+                }) if *binary => {
+                    state.selected = 1;
+                    return Ok(true);
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char('n' | 'N'),
+                    ..
+                }) if *binary => {
+                    state.selected = 0;
+                    return Ok(true);
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Enter,
+                    modifiers: KeyModifiers::NONE,
+                    ..
+                }) => return Ok(true),
+                _ => false,
+            },
+            Component::Spinner { .. } => false,
+            Component::Progress { .. } => false,
+            Component::Typer { looping, .. } => match event {
+                Event::Key(_) if *looping => return Ok(true),
+                _ => false,
+            },
+            Component::Choose {
+                filter,
+                height,
+                strict,
+                mouse,
+                state,
+                ..
+            } => {
+                let had_error = state.error.take().is_some();
+                let should_redraw = match event {
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char(c),
+                        modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+                        ..
+                    }) if *filter => {
+                        state.query.push(*c);
+                        state.refilter();
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Backspace,
+                        ..
+                    }) if *filter => {
+                        state.query.pop();
+                        state.refilter();
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Down | KeyCode::Char('j'),
+                        ..
+                    }) => state.move_cursor(true),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Up | KeyCode::Char('k'),
+                        ..
+                    }) => state.move_cursor(false),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::PageDown,
+                        ..
+                    }) => state.page(true, height.unwrap_or(state.visible.len())),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::PageUp,
+                        ..
+                    }) => state.page(false, height.unwrap_or(state.visible.len())),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('g'),
+                        ..
+                    }) => state.first(),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('G'),
+                        ..
+                    }) => state.last(),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('a'),
+                        modifiers: KeyModifiers::NONE,
+                        ..
+                    }) if state.selections.get() > 1 => {
+                        // Select in order until the cap is reached
+                        for &i in &state.visible {
+                            if state.chosen.len() == state.selections.get() {
+                                break;
+                            }
+                            if !state.headers[i] && !state.chosen.contains(&i) {
+                                state.chosen.push(i, ());
+                            }
+                        }
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('A'),
+                        ..
+                    })
+                    | Event::Key(KeyEvent {
+                        code: KeyCode::Char('a'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }) if state.selections.get() > 1 => {
+                        state.chosen.clear();
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char(' ') | KeyCode::Tab,
+                        ..
+                    }) => {
+                        state.toggle(*strict);
+                        true
+                    }
+                    Event::Mouse(MouseEvent {
+                        kind: MouseEventKind::Down(MouseButton::Left),
+                        row,
+                        ..
+                    }) if *mouse => {
+                        // map the row back to an option, ignoring clicks elsewhere
+                        let shown = height.unwrap_or(state.visible.len());
+                        let loc = row
+                            .checked_sub(state.list_top)
+                            .map(|row| state.offset + row as usize)
+                            .filter(|loc| {
+                                *loc < state.visible.len()
+                                    && *loc < state.offset + shown
+                                    && state.selectable(*loc)
+                            });
+                        match loc {
+                            Some(loc) => {
+                                state.cursor_loc = loc;
+                                state.toggle(*strict);
+                                true
+                            }
+                            None => false,
+                        }
+                    }
+                    Event::Mouse(MouseEvent {
+                        kind: MouseEventKind::ScrollDown,
+                        ..
+                    }) if *mouse => state.move_cursor(true),
+                    Event::Mouse(MouseEvent {
+                        kind: MouseEventKind::ScrollUp,
+                        ..
+                    }) if *mouse => state.move_cursor(false),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Enter,
+                        ..
+                    }) => {
+                        if state.chosen.len() >= state.min {
+                            return Ok(true);
+                        }
+                        screen.ring()?;
+                        false
+                    }
+                    _ => false,
+                };
+                if state.error.is_some() {
+                    screen.ring()?;
+                }
+                state.scroll(*height);
+                should_redraw || had_error
             }
-            Component::Typer {
-                state, speed, wait, ..
-            } => {
-                if state.done_printing {
-                    if state.last_updated.elapsed() > *wait {
-                        return Ok(true);
+            Component::Filter { height, state, .. } => {
+                let should_redraw = match event {
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char(c),
+                        modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+                        ..
+                    }) => {
+                        state.query.push(*c);
+                        state.refilter();
+                        true
                     }
-                } else {
-                    if state.last_updated.elapsed() > *speed {
-                        let c = state.iter.next();
-                        if let Some(c) = c {
-                            execute!(screen, Print(c)).drop_error()?;
-                            state.last_updated = Instant::now();
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Backspace,
+                        ..
+                    }) => {
+                        state.query.pop();
+                        state.refilter();
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Down,
+                        ..
+                    }) if state.cursor_loc + 1 < state.matches.len() => {
+                        state.cursor_loc += 1;
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Up, ..
+                    }) if state.cursor_loc != 0 => {
+                        state.cursor_loc -= 1;
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Enter,
+                        ..
+                    }) if state.current().is_some() => return Ok(true),
+                    _ => false,
+                };
+                state.scroll(*height);
+                should_redraw
+            }
+            Component::Pager { height, state, .. } => {
+                let pending_g = std::mem::take(&mut state.pending_g);
+                let page = *height as isize;
+                match event {
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('q'),
+                        ..
+                    }) => return Ok(true),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Down | KeyCode::Char('j'),
+                        ..
+                    }) => state.scroll_by(1, *height),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Up | KeyCode::Char('k'),
+                        ..
+                    }) => state.scroll_by(-1, *height),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::PageDown | KeyCode::Char(' '),
+                        ..
+                    }) => state.scroll_by(page, *height),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::PageUp,
+                        ..
+                    }) => state.scroll_by(-page, *height),
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('g'),
+                        ..
+                    }) => {
+                        if pending_g {
+                            state.top()
                         } else {
-                            state.done_printing = true;
+                            state.pending_g = true;
+                            false
                         }
                     }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('G'),
+                        ..
+                    }) => state.bottom(*height),
+                    _ => false,
                 }
-                false
             }
-            _ => false,
-        };
-
-        if should_redraw {
-            self.draw(screen)?;
-        }
-
-        Ok(false)
-    }
-
-    /// Update the component with keystroke event
-    /// Returns Ok(true) if component is in the terminal state
-    /// # Errors if unable to draw to the terminal
-    pub fn update(&mut self, event: &Event, screen: &mut Stderr) -> Result<bool, ()> {
-        let should_redraw: bool = match self {
-            Component::Text {
-                state: TextState { input },
-                ..
-            } => match event {
-                Event::Key(KeyEvent {
+            Component::Menu { height, state, .. } => {
+                // hotkeys take priority over navigation
+                if let Event::Key(KeyEvent {
                     code: KeyCode::Char(c),
+                    modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
                     ..
-                }) => {
-                    input.push(*c);
-                    true
+                }) = event
+                {
+                    if let Some(i) = state.find_accelerator(*c) {
+                        state.cursor_loc = i;
+                        return Ok(true);
+                    }
                 }
+
+                let should_redraw = match event {
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Down | KeyCode::Char('j'),
+                        ..
+                    }) if state.cursor_loc + 1 < state.items.len() => {
+                        state.cursor_loc += 1;
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Up | KeyCode::Char('k'),
+                        ..
+                    }) if state.cursor_loc != 0 => {
+                        state.cursor_loc -= 1;
+                        true
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Enter,
+                        ..
+                    }) => return Ok(true),
+                    _ => false,
+                };
+                state.scroll(*height);
+                should_redraw
+            }
+            Component::Timer { state, .. } => match event {
+                Event::Key(_) => {
+                    state.skipped = true;
+                    return Ok(true);
+                }
+                _ => false,
+            },
+            Component::Stopwatch { state, .. } => match event {
                 Event::Key(KeyEvent {
-                    code: KeyCode::Backspace,
+                    code: KeyCode::Enter | KeyCode::Char(' '),
                     ..
                 }) => {
-                    input.pop();
-                    true
+                    state.stop();
+                    return Ok(true);
                 }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Enter,
-                    modifiers: KeyModifiers::NONE,
-                    ..
-                }) => return Ok(true),
                 _ => false,
             },
-            Component::Confirm { state, .. } => match event {
+            Component::Slider { state, .. } => match event {
                 Event::Key(KeyEvent {
-                    code: KeyCode::Right,
+                    code: KeyCode::Left | KeyCode::Char('h' | '-'),
                     ..
-                }) => {
-                    state.confirmed = true;
-                    true
-                }
+                }) => state.step_by(-1),
                 Event::Key(KeyEvent {
-                    code: KeyCode::Left,
+                    code: KeyCode::Right | KeyCode::Char('l' | '+'),
                     ..
-                }) => {
-                    state.confirmed = false;
-                    true
-                }
+                }) => state.step_by(1),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Home,
+                    ..
+                }) => state.step_by(isize::MIN),
+                Event::Key(KeyEvent {
+                    code: KeyCode::End, ..
+                }) => state.step_by(isize::MAX),
                 Event::Key(KeyEvent {
                     code: KeyCode::Enter,
-                    modifiers: KeyModifiers::NONE,
                     ..
                 }) => return Ok(true),
                 _ => false,
             },
-            Component::Spinner { .. } => false,
-            Component::Typer { .. } => false,
-            Component::Choose { inexact, state, .. } => match event {
-                Event::Key(KeyEvent {
-                    code: KeyCode::Down,
-                    ..
-                }) => {
-                    if state.cursor_loc != state.choices.len() - 1 {
-                        state.cursor_loc += 1;
-                        true
+            Component::Pause { .. } => match event {
+                Event::Key(_) => return Ok(true),
+                _ => false,
+            },
+        };
+
+        if should_redraw {
+            self.draw(screen)?;
+        }
+
+        Ok(false)
+    }
+
+    pub fn draw(&mut self, screen: &mut Screen) -> Result<(), ()> {
+        let origin = screen.origin;
+        let corner = (screen.padding, origin + screen.padding);
+        // content starts inside the border, if any
+        let padding = screen.padding + screen.border.inset();
+        let top = corner.1 + screen.border.inset();
+        queue!(screen, MoveTo(0, origin), Clear(ClearType::FromCursorDown)).drop_error()?;
+        if !matches!(screen.border, Border::None) {
+            draw_border(screen, corner, self.extent())?;
+        }
+        queue!(screen, MoveTo(padding, top)).drop_error()?;
+
+        match self {
+            Component::Text {
+                width,
+                placeholder,
+                prefix,
+                style,
+                mask,
+                validate,
+                cursor_style,
+                cursor_blink,
+                show_count,
+                suggestion,
+                state,
+                ..
+            } => {
+                // the terminal's cursor goes where the reverse video one would be
+                let shape = cursor_style.shape(*cursor_blink);
+                let reverse = *cursor_style == CursorStyle::Reverse;
+                let column = padding + prefix.width() as u16;
+
+                // clear the error once the input matches
+                if validate.as_ref().is_none_or(|re| re.is_match(&state.input)) {
+                    state.error = None;
+                }
+                if let Some(error) = &state.error {
+                    queue!(
+                        screen,
+                        MoveTo(padding, top + 2),
+                        SetForegroundColor(Color::Red),
+                        SetAttribute(Attribute::Dim),
+                        Print(error),
+                        SetAttribute(Attribute::Reset),
+                        ResetColor
+                    )
+                    .drop_error()?;
+                }
+
+                // count at the right edge, past the cursor at the end of the input
+                if *show_count {
+                    let count = state.input.graphemes(true).count();
+                    let unit = if count == 1 { "char" } else { "chars" };
+                    queue!(
+                        screen,
+                        MoveTo(column + *width as u16 + 2, top),
+                        SetAttribute(Attribute::Dim),
+                        Print(format!("{count} {unit}")),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                }
+
+                queue!(screen, MoveTo(padding, top)).drop_error()?;
+
+                style.apply(screen)?;
+                // dim the prefix once the limit is reached
+                if state.char_limit != 0 && state.input.graphemes(true).count() >= state.char_limit
+                {
+                    queue!(screen, SetAttribute(Attribute::Dim)).drop_error()?;
+                }
+                queue!(
+                    screen,
+                    Print(prefix),
+                    SetAttribute(Attribute::Reset),
+                    ResetColor
+                )
+                .drop_error()?;
+
+                let ghost = suggestion
+                    .as_deref()
+                    .and_then(|suggestion| state.ghost(suggestion));
+                if state.input.is_empty() && ghost.is_none() {
+                    // show as much of the placeholder as fits
+                    let end = column_byte_index(placeholder, *width);
+                    queue!(
+                        screen,
+                        SetAttribute(Attribute::Italic),
+                        SetAttribute(Attribute::Dim),
+                        Print(&placeholder[..end]),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+
+                    if let Some(shape) = shape {
+                        queue!(screen, MoveTo(column, top), shape, Show).drop_error()?;
+                    }
+                    return Ok(());
+                }
+
+                // mask one-for-one per grapheme
+                let graphemes: Vec<String> = state
+                    .input
+                    .graphemes(true)
+                    .map(|g| match mask {
+                        Some(m) if !state.revealed => m.to_string(),
+                        _ => g.to_owned(),
+                    })
+                    .collect();
+                // columns taken up by the graphemes from `offset` up to `end`
+                let span = |offset: usize, end: usize| -> usize {
+                    graphemes[offset..end].iter().map(|g| g.width()).sum()
+                };
+
+                // scroll the window so that the cursor, and whatever is under it, stays visible
+                let under = graphemes.get(state.cursor).map_or(1, |g| g.width());
+                if state.cursor < state.offset {
+                    state.offset = state.cursor;
+                }
+                while state.offset < state.cursor
+                    && span(state.offset, state.cursor) + under > *width
+                {
+                    state.offset += 1;
+                }
+
+                let mut used = 0;
+                for (i, g) in graphemes.iter().enumerate().skip(state.offset) {
+                    used += g.width();
+                    if used > *width {
+                        break;
+                    }
+                    if reverse && i == state.cursor {
+                        queue!(
+                            screen,
+                            SetAttribute(Attribute::Reverse),
+                            Print(g),
+                            SetAttribute(Attribute::Reset)
+                        )
+                        .drop_error()?;
                     } else {
-                        false
+                        queue!(screen, Print(g)).drop_error()?;
+                    }
+                }
+
+                if let Some(ghost) = ghost {
+                    // the rest of the suggestion, starting under the cursor
+                    let room = width.saturating_sub(span(state.offset, state.cursor));
+                    let end = column_byte_index(ghost, room);
+                    for (i, g) in ghost[..end].graphemes(true).enumerate() {
+                        queue!(screen, SetAttribute(Attribute::Dim)).drop_error()?;
+                        if reverse && i == 0 {
+                            queue!(screen, SetAttribute(Attribute::Reverse)).drop_error()?;
+                        }
+                        queue!(screen, Print(g), SetAttribute(Attribute::Reset)).drop_error()?;
+                    }
+                } else if reverse && state.cursor == graphemes.len() {
+                    // cursor past the last grapheme
+                    queue!(
+                        screen,
+                        SetAttribute(Attribute::Reverse),
+                        Print(" "),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                }
+
+                if let Some(shape) = shape {
+                    let column = column + span(state.offset, state.cursor) as u16;
+                    queue!(screen, MoveTo(column, top), shape, Show).drop_error()?;
+                }
+
+                Ok(())
+            }
+            Component::Textarea {
+                width,
+                height,
+                placeholder,
+                submit_key,
+                state,
+            } => {
+                if state.is_empty() {
+                    let end = grapheme_byte_index(placeholder, *width);
+                    queue!(
+                        screen,
+                        MoveTo(padding, top),
+                        SetAttribute(Attribute::Italic),
+                        SetAttribute(Attribute::Dim),
+                        Print(&placeholder[..end]),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                }
+
+                // scroll the viewport so that the cursor stays visible
+                let (cursor_row, cursor_col) = state.cursor_position(*width);
+                if cursor_row < state.offset {
+                    state.offset = cursor_row;
+                } else if cursor_row >= state.offset + *height {
+                    state.offset = cursor_row + 1 - *height;
+                }
+
+                let rows = state.wrapped(*width);
+                for (i, (row, start)) in rows
+                    .into_iter()
+                    .skip(state.offset)
+                    .take(*height)
+                    .enumerate()
+                {
+                    let is_cursor_row = state.offset + i == cursor_row;
+                    queue!(screen, MoveTo(padding, top + i as u16)).drop_error()?;
+
+                    let mut printed = 0;
+                    for (j, g) in state.lines[row]
+                        .graphemes(true)
+                        .skip(start)
+                        .take(*width)
+                        .enumerate()
+                    {
+                        if is_cursor_row && j == cursor_col {
+                            queue!(
+                                screen,
+                                SetAttribute(Attribute::Reverse),
+                                Print(g),
+                                SetAttribute(Attribute::Reset)
+                            )
+                            .drop_error()?;
+                        } else {
+                            queue!(screen, Print(g)).drop_error()?;
+                        }
+                        printed += 1;
+                    }
+
+                    // cursor past the last grapheme
+                    if is_cursor_row && cursor_col == printed && !state.is_empty() {
+                        queue!(
+                            screen,
+                            SetAttribute(Attribute::Reverse),
+                            Print(" "),
+                            SetAttribute(Attribute::Reset)
+                        )
+                        .drop_error()?;
+                    }
+                }
+
+                queue!(
+                    screen,
+                    MoveTo(padding, top + *height as u16 + 1),
+                    SetAttribute(Attribute::Dim),
+                    SetAttribute(Attribute::Italic),
+                    Print(format!(
+                        "Ctrl-{} to submit",
+                        submit_key.to_ascii_uppercase()
+                    )),
+                    SetAttribute(Attribute::Reset)
+                )
+                .drop_error()?;
+
+                Ok(())
+            }
+            Component::Confirm {
+                text,
+                width,
+                style,
+                theme,
+                height,
+                buttons,
+                timeout,
+                require_text,
+                state:
+                    ConfirmState {
+                        selected,
+                        countdown,
+                        typed,
+                        ..
+                    },
+                ..
+            } => {
+                let lines = wrap(text, *width);
+                let input_rows = require_text.is_some() as u16 * 2;
+                let line = start_line(top, lines.len() + 2 + input_rows as usize, *height);
+                style.apply(screen)?;
+                for (i, text) in lines.iter().enumerate() {
+                    queue!(screen, MoveTo(padding, line + i as u16), Print(text)).drop_error()?;
+                }
+                queue!(screen, SetAttribute(Attribute::Reset), ResetColor).drop_error()?;
+
+                let buttons_line = line + lines.len() as u16 + 1 + input_rows;
+                let locked = require_text
+                    .as_ref()
+                    .is_some_and(|required| typed != required);
+                if let Some(required) = require_text {
+                    queue!(
+                        screen,
+                        MoveTo(padding, line + lines.len() as u16 + 1),
+                        SetAttribute(Attribute::Dim),
+                        Print(format!("Type \"{required}\" to confirm: ")),
+                        SetAttribute(Attribute::Reset),
+                        Print(typed),
+                        SetAttribute(Attribute::Reverse),
+                        Print(" "),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                }
+
+                queue!(screen, MoveTo(padding, buttons_line)).drop_error()?;
+                for (i, button) in buttons.iter().enumerate() {
+                    if i > 0 {
+                        queue!(screen, Print("  ")).drop_error()?;
+                    }
+                    // yes stays dimmed until the required text is typed
+                    if locked && i == 1 {
+                        queue!(screen, SetAttribute(Attribute::Dim)).drop_error()?;
                     }
+                    queue!(
+                        screen,
+                        SetBackgroundColor(theme.color(i == *selected)),
+                        Print(button),
+                        ResetColor,
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                }
+
+                if timeout.is_some() {
+                    queue!(
+                        screen,
+                        SetAttribute(Attribute::Dim),
+                        Print(format!("  {countdown}s")),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                }
+
+                Ok(())
+            }
+            Component::Spinner {
+                text,
+                title,
+                align,
+                width,
+                output_height,
+                timeout,
+                show_time,
+                state:
+                    SpinnerState {
+                        chars,
+                        progress,
+                        lines,
+                        started,
+                        step,
+                        total,
+                        ..
+                    },
+                ..
+            } => {
+                let c = &chars[*progress];
+                let text = if *total > 1 {
+                    format!("{text} ({step}/{total})")
+                } else {
+                    text.clone()
+                };
+                let line = match align {
+                    SpinnerAlign::Left => format!("{c}  {text}"),
+                    SpinnerAlign::Right => format!("{text}  {c}"),
+                };
+
+                // the spinner goes below the title, if any
+                let mut row = top;
+                if let Some(title) = title {
+                    queue!(
+                        screen,
+                        MoveTo(padding, row),
+                        SetAttribute(Attribute::Bold),
+                        Print(title),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                    row += 1;
+                }
+
+                queue!(screen, MoveTo(padding, row), Print(line)).drop_error()?;
+
+                if *show_time {
+                    queue!(
+                        screen,
+                        SetAttribute(Attribute::Dim),
+                        Print(format!("  {}", format_duration(started.elapsed()))),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                }
+
+                if let Some(timeout) = timeout {
+                    let remaining = timeout.saturating_sub(started.elapsed()).as_secs() + 1;
+                    queue!(
+                        screen,
+                        SetAttribute(Attribute::Dim),
+                        Print(format!("  {remaining}s")),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                }
+
+                // show the tail of the output
+                let skip = lines.len().saturating_sub(*output_height);
+                for (i, line) in lines.iter().skip(skip).enumerate() {
+                    let end = grapheme_byte_index(line, *width);
+                    queue!(
+                        screen,
+                        MoveTo(padding, row + 2 + i as u16),
+                        SetAttribute(Attribute::Dim),
+                        Print(&line[..end]),
+                        SetAttribute(Attribute::Reset)
+                    )
+                    .drop_error()?;
+                }
+
+                Ok(())
+            }
+            Component::Typer {
+                width,
+                text,
+                color,
+                bold,
+                state,
+                ..
+            } => {
+                if let Some(color) = color {
+                    queue!(screen, SetForegroundColor(*color)).drop_error()?;
                 }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Up, ..
-                }) => {
-                    if state.cursor_loc != 0 {
-                        state.cursor_loc -= 1;
-                        true
-                    } else {
-                        false
-                    }
+                if *bold {
+                    queue!(screen, SetAttribute(Attribute::Bold)).drop_error()?;
                 }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Char(' '),
-                    ..
-                }) => {
-                    let curstate = state.chosen.get(&state.cursor_loc).is_some();
-                    if curstate {
-                        // Remove from selection
-                        state.chosen.pop(&state.cursor_loc);
-                    } else {
-                        // Add to selection
-                        state.chosen.push(state.cursor_loc, ());
+                // wrap all of the text so words don't jump to the next line as they
+                // grow, and reveal as many of its visible graphemes as were typed
+                let mut left = state
+                    .typed
+                    .graphemes(true)
+                    .filter(|g| !g.trim().is_empty())
+                    .count();
+                let mut end = (padding, top);
+                for (i, line) in wrap(text, *width).iter().enumerate() {
+                    if left == 0 {
+                        break;
                     }
-                    true
-                }
-                Event::Key(KeyEvent {
-                    code: KeyCode::Enter,
-                    ..
-                }) => {
-                    if *inexact || state.chosen.len() == state.selections.get() {
-                        return Ok(true);
+                    let mut shown = String::new();
+                    for g in line.graphemes(true) {
+                        if left == 0 {
+                            break;
+                        }
+                        if !g.trim().is_empty() {
+                            left -= 1;
+                        }
+                        shown.push_str(g);
                     }
-                    false
+                    let row = top + i as u16;
+                    queue!(screen, MoveTo(padding, row), Print(&shown)).drop_error()?;
+                    end = (padding + shown.width() as u16, row);
                 }
-                _ => false,
-            },
-        };
-
-        // for now, always redraw
-        if should_redraw {
-            self.draw(screen)?;
-        }
-
-        Ok(false)
-    }
+                queue!(screen, SetAttribute(Attribute::Reset), ResetColor).drop_error()?;
 
-    pub fn draw(&mut self, screen: &mut Stderr) -> Result<(), ()> {
-        // TODO: Use styling
-        let padding = 2;
-        execute!(screen, Clear(ClearType::All), MoveTo(padding, padding)).drop_error()?;
+                if !state.done_printing && state.cursor_visible {
+                    queue!(screen, MoveTo(end.0, end.1), Print("▉")).drop_error()?;
+                }
 
-        match self {
-            Component::Text {
+                Ok(())
+            }
+            Component::Choose {
+                text,
+                style,
+                theme,
+                filter,
+                height,
+                state,
                 width,
-                placeholder,
-                prefix,
-                state: TextState { input },
+                cursor_prefix,
+                selected_string,
+                unselected_string,
+                ..
             } => {
-                execute!(screen, MoveTo(padding, padding)).drop_error()?;
-
-                let (is_bg, to_print) = match input.as_str() {
-                    "" => {
-                        // show first n graphemes of placeholder
-                        let end = placeholder
-                            .grapheme_indices(true)
-                            .nth(*width)
-                            .map(|(i, _)| i)
-                            .unwrap_or(placeholder.len());
-                        (true, &placeholder[..end])
-                    }
-                    s => {
-                        // show last n graphemes of input
-                        let start = s
-                            .grapheme_indices(true)
-                            .rev()
-                            .nth(*width - 1)
-                            .map(|(i, _)| i)
-                            .unwrap_or(0);
-                        (false, &s[start..])
+                let content_height = state.content_height(*filter, *height);
+                let mut line = start_line(top, content_height, *height);
+                queue!(screen, MoveTo(padding, line)).drop_error()?;
+                style.apply(screen)?;
+                queue!(
+                    screen,
+                    Print(text),
+                    SetAttribute(Attribute::Reset),
+                    ResetColor,
+                    MoveTo(padding, line + 1),
+                    SetAttribute(Attribute::Dim),
+                    SetAttribute(Attribute::Italic)
+                )
+                .drop_error()?;
+                if let Some(error) = &state.error {
+                    queue!(screen, SetForegroundColor(Color::Red), Print(error)).drop_error()?;
+                } else {
+                    queue!(
+                        screen,
+                        Print(match (state.min, state.selections.get()) {
+                            (min, max) if min == max => format!("Select exactly {max}"),
+                            (0, max) => format!("Select at most {max}"),
+                            (min, max) => format!("Select {min} to {max}"),
+                        })
+                    )
+                    .drop_error()?;
+                }
+                queue!(screen, SetAttribute(Attribute::Reset), ResetColor).drop_error()?;
+
+                if *filter {
+                    queue!(
+                        screen,
+                        MoveTo(padding, line + 2),
+                        Print(format!("/ {}", state.query))
+                    )
+                    .drop_error()?;
+                    line += 1;
+                }
+
+                line += 3;
+                state.list_top = line;
+                let height = height.unwrap_or(state.visible.len());
+                // other rows line up with the one under the cursor
+                let blank_prefix = " ".repeat(cursor_prefix.width());
+                let label_width = label_width(*width, cursor_prefix, selected_string);
+                let rows = state.visible.iter().enumerate().skip(state.offset);
+                for (i, &choice_i) in rows.take(height) {
+                    let choice = &state.choices[choice_i];
+                    if state.headers[choice_i] {
+                        queue!(
+                            screen,
+                            MoveTo(padding, line),
+                            SetAttribute(Attribute::Bold),
+                            Print(truncate(choice, *width)),
+                            SetAttribute(Attribute::Reset)
+                        )
+                        .drop_error()?;
+                        line += 1;
+                        continue;
                     }
-                };
 
-                // set style
-                if is_bg {
-                    execute!(
+                    let prefix = if i == state.cursor_loc {
+                        queue!(screen, SetForegroundColor(theme.color(true))).drop_error()?;
+                        cursor_prefix.as_str()
+                    } else {
+                        &blank_prefix
+                    };
+
+                    let selection: &str = if state.chosen.contains(&choice_i) {
+                        selected_string
+                    } else {
+                        unselected_string
+                    };
+
+                    queue!(
                         screen,
-                        SetAttribute(Attribute::Italic),
-                        SetAttribute(Attribute::Dim)
+                        MoveTo(padding, line),
+                        Print(format!(
+                            "{prefix}{selection} {}",
+                            truncate(choice, label_width)
+                        )),
+                        ResetColor
+                    )
+                    .drop_error()?;
+
+                    line += 1;
+                }
+
+                let below = state.visible.len().saturating_sub(state.offset + height);
+                if below > 0 {
+                    queue!(
+                        screen,
+                        MoveTo(padding, line),
+                        SetAttribute(Attribute::Dim),
+                        Print(format!("({below} more below)")),
+                        SetAttribute(Attribute::Reset)
                     )
                     .drop_error()?;
                 }
 
-                execute!(
+                Ok(())
+            }
+            Component::Filter {
+                prefix,
+                theme,
+                height,
+                state,
+            } => {
+                queue!(
                     screen,
+                    MoveTo(padding, top),
                     Print(prefix),
-                    Print(to_print),
+                    Print(&state.query),
+                    SetAttribute(Attribute::Reverse),
+                    Print(" "),
+                    SetAttribute(Attribute::Reset),
+                    MoveTo(padding, top + 1),
+                    SetAttribute(Attribute::Dim),
+                    Print(format!("{}/{}", state.matches.len(), state.choices.len())),
                     SetAttribute(Attribute::Reset)
                 )
                 .drop_error()?;
 
+                let rows = state.matches.iter().enumerate().skip(state.offset);
+                for (row, (i, &choice_i)) in rows.take(*height).enumerate() {
+                    let choice = &state.choices[choice_i];
+                    let current = i == state.cursor_loc;
+                    queue!(screen, MoveTo(padding, top + 3 + row as u16)).drop_error()?;
+                    if current {
+                        queue!(screen, SetForegroundColor(theme.color(true))).drop_error()?;
+                    }
+
+                    // show why the choice matched
+                    let matched = fuzzy_match(&state.query, choice)
+                        .map_or_else(Vec::new, |(_, positions)| positions);
+                    for (j, c) in choice.chars().enumerate() {
+                        if !matched.contains(&j) {
+                            queue!(screen, Print(c)).drop_error()?;
+                            continue;
+                        }
+                        queue!(
+                            screen,
+                            SetForegroundColor(theme.color(true)),
+                            SetAttribute(Attribute::Bold),
+                            Print(c),
+                            SetAttribute(Attribute::NormalIntensity)
+                        )
+                        .drop_error()?;
+                        if !current {
+                            queue!(screen, ResetColor).drop_error()?;
+                        }
+                    }
+                    queue!(screen, ResetColor).drop_error()?;
+                }
+
                 Ok(())
             }
-            Component::Confirm {
-                text,
-                padded_no,
-                padded_yes,
-                state: ConfirmState { confirmed },
+            Component::Pager {
+                width,
+                height,
+                state,
             } => {
-                // TODO: Truncate/wrap text
-                execute!(
+                let visible = state.lines.iter().skip(state.offset).take(*height);
+                for (row, line) in visible.enumerate() {
+                    let end = grapheme_byte_index(line, *width);
+                    queue!(
+                        screen,
+                        MoveTo(padding, top + row as u16),
+                        Print(&line[..end])
+                    )
+                    .drop_error()?;
+                }
+
+                let last = (state.offset + *height).min(state.lines.len());
+                queue!(
                     screen,
-                    MoveTo(padding, padding),
-                    Print(text),
-                    MoveTo(padding, padding + 2),
-                    SetBackgroundColor(get_bg_color(!*confirmed)),
-                    Print(padded_no),
-                    ResetColor,
-                    Print("  "),
-                    SetBackgroundColor(get_bg_color(*confirmed)),
-                    Print(padded_yes),
-                    ResetColor
+                    MoveTo(padding, top + *height as u16 + 1),
+                    SetAttribute(Attribute::Dim),
+                    SetAttribute(Attribute::Italic),
+                    Print(format!(
+                        "Lines {}-{} of {}, q to quit",
+                        (state.offset + 1).min(last),
+                        last,
+                        state.lines.len()
+                    )),
+                    SetAttribute(Attribute::Reset)
                 )
                 .drop_error()?;
 
                 Ok(())
             }
-            Component::Spinner {
-                text,
-                state: SpinnerState {
-                    chars, progress, ..
-                },
-                ..
+            Component::Progress {
+                width,
+                theme,
+                state,
             } => {
-                let c = &chars[*progress];
-
-                execute!(
+                queue!(
                     screen,
-                    MoveTo(padding, padding),
-                    Print(format!("{c}  {text}")),
+                    MoveTo(padding, top),
+                    SetForegroundColor(theme.color(true)),
+                    Print(state.bar(*width)),
+                    ResetColor,
+                    Print(format!(" {:>3.0}%", state.percent))
                 )
                 .drop_error()?;
 
                 Ok(())
             }
-            Component::Typer { .. } => Ok(()),
-            Component::Choose {
+            Component::Menu {
                 text,
+                style,
+                theme,
+                height,
                 state,
-                selected_string,
-                unselected_string,
-                inexact,
             } => {
-                let mut line = padding;
-                execute!(
+                queue!(screen, MoveTo(padding, top)).drop_error()?;
+                style.apply(screen)?;
+                queue!(
                     screen,
-                    MoveTo(padding, line),
                     Print(text),
-                    MoveTo(padding, line + 1),
+                    SetAttribute(Attribute::Reset),
+                    ResetColor
+                )
+                .drop_error()?;
+
+                let rows = state.items.iter().enumerate().skip(state.offset);
+                for (row, (i, item)) in rows.take(*height).enumerate() {
+                    queue!(screen, MoveTo(padding, top + 2 + row as u16)).drop_error()?;
+                    if i == state.cursor_loc {
+                        queue!(screen, SetForegroundColor(theme.color(true))).drop_error()?;
+                    }
+
+                    // underline the hotkey
+                    match (item.key, item.accelerator()) {
+                        (Some(start), Some(key)) => {
+                            let end = start + key.len_utf8();
+                            queue!(
+                                screen,
+                                Print(&item.label[..start]),
+                                SetAttribute(Attribute::Underlined),
+                                Print(key),
+                                SetAttribute(Attribute::NoUnderline),
+                                Print(&item.label[end..])
+                            )
+                            .drop_error()?;
+                        }
+                        _ => queue!(screen, Print(&item.label)).drop_error()?,
+                    }
+                    queue!(screen, ResetColor).drop_error()?;
+                }
+
+                Ok(())
+            }
+            Component::Timer { text, style, state } => {
+                style.apply(screen)?;
+                queue!(
+                    screen,
+                    Print(text),
+                    SetAttribute(Attribute::Reset),
+                    ResetColor,
+                    Print(format!(
+                        " {}",
+                        format_duration(Duration::from_secs(state.remaining))
+                    )),
+                    MoveTo(padding, top + 2),
                     SetAttribute(Attribute::Dim),
                     SetAttribute(Attribute::Italic),
+                    Print("Press any key to skip"),
+                    SetAttribute(Attribute::Reset)
+                )
+                .drop_error()?;
+
+                Ok(())
+            }
+            Component::Stopwatch { text, style, state } => {
+                style.apply(screen)?;
+                queue!(
+                    screen,
+                    Print(text),
+                    SetAttribute(Attribute::Reset),
+                    ResetColor,
                     Print(format!(
-                        "Select {} {}",
-                        if *inexact { "at most" } else { "exactly" },
-                        state.selections.get()
+                        " {}",
+                        format_duration(Duration::from_secs(state.elapsed))
                     )),
+                    MoveTo(padding, top + 2),
+                    SetAttribute(Attribute::Dim),
+                    SetAttribute(Attribute::Italic),
+                    Print("Press Enter or Space to stop"),
                     SetAttribute(Attribute::Reset)
                 )
                 .drop_error()?;
 
-                line += 3;
-                for (choice_i, choice) in state.choices.iter().enumerate() {
-                    if choice_i == state.cursor_loc {
-                        execute!(screen, SetForegroundColor(get_bg_color(true))).drop_error()?;
-                    }
-
-                    let selection: &str = if state.chosen.contains(&choice_i) {
-                        &selected_string
-                    } else {
-                        &unselected_string
-                    };
-
-                    execute!(
-                        screen,
-                        MoveTo(padding, line),
-                        Print(format!("{selection} {choice}")),
-                        ResetColor
-                    )
-                    .drop_error()?;
+                Ok(())
+            }
+            Component::Slider {
+                text,
+                width,
+                style,
+                theme,
+                state,
+            } => {
+                style.apply(screen)?;
+                queue!(
+                    screen,
+                    Print(text),
+                    SetAttribute(Attribute::Reset),
+                    ResetColor,
+                    MoveTo(padding, top + 2),
+                    SetForegroundColor(theme.color(true)),
+                    Print(state.bar(*width)),
+                    ResetColor,
+                    Print(format!(" {}", state.value()))
+                )
+                .drop_error()?;
 
-                    line += 1;
+                Ok(())
+            }
+            Component::Pause { style, state, .. } => {
+                style.apply(screen)?;
+                for (i, line) in state.lines.iter().enumerate() {
+                    queue!(screen, MoveTo(padding, top + i as u16), Print(line)).drop_error()?;
                 }
+                queue!(screen, SetAttribute(Attribute::Reset), ResetColor).drop_error()?;
 
                 Ok(())
             }
@@ -688,21 +4234,76 @@ impl<'a> Component<'a> {
     }
 }
 
-fn main() -> Result<(), ()> {
-    let opts = Opts::from_args();
+/// Run the component on the terminal until it finishes,
+/// returning whether it was interrupted, cancelled or timed out
+fn interact(component: &mut Component, opts: &Opts) -> Result<(bool, bool, bool), ()> {
+    let mut screen = Screen {
+        out: BufWriter::new(stderr()),
+        origin: 0,
+        padding: opts.padding,
+        bell: opts.bell,
+        border: opts.border,
+    };
 
-    // Create component
-    let mut component = Component::from_opts(&opts);
+    // put the terminal back before a panic message is printed
+    let alt_screen = !opts.no_alt_screen;
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        disable_raw_mode().ok(); // swallow error
+        let mut out = stderr();
+        execute!(
+            out,
+            DisableMouseCapture,
+            SetCursorStyle::DefaultUserShape,
+            Show
+        )
+        .ok();
+        if alt_screen {
+            execute!(out, LeaveAlternateScreen).ok();
+        }
+        default_hook(info);
+    }));
+
+    if opts.no_alt_screen {
+        // make room below the cursor for the component, its padding and border,
+        // and a couple of rows that may appear later, like a validation error
+        let (_, terminal_rows) = size().drop_error()?;
+        let content = component.extent().1 as u16 + 2;
+        let rows = (opts.padding + 2 * opts.border.inset() + content).min(terminal_rows);
+        execute!(screen, Print("\n".repeat(rows as usize)), MoveUp(rows)).drop_error()?;
+        enable_raw_mode().drop_error()?;
+        screen.origin = cursor_row(&mut screen)?.min(terminal_rows - rows);
+        execute!(screen, Hide).drop_error()?;
+    } else {
+        // enter the alternate screen
+        execute!(screen, EnterAlternateScreen, Hide).drop_error()?;
+        enable_raw_mode().drop_error()?;
+    }
 
-    let mut screen = stderr();
+    // Text may change the shape of the terminal's cursor
+    let cursor_shaped = matches!(
+        *component,
+        Component::Text { cursor_style, .. } if cursor_style.shape(false).is_some()
+    );
 
-    // enter the alternate screen
-    execute!(screen, EnterAlternateScreen, Hide).drop_error()?;
-    enable_raw_mode().drop_error()?;
+    // only choose handles the mouse
+    let mouse = matches!(*component, Component::Choose { mouse: true, .. });
+    if mouse {
+        execute!(screen, EnableMouseCapture).drop_error()?;
+    }
 
     // Component setup.
     component.draw(&mut screen)?;
     let mut interrupted = false;
+    let mut cancelled = false;
+    let mut timed_out = false;
+    let started = Instant::now();
+    let timeout = opts.timeout.map(Duration::from_secs);
+    let poll_interval = component
+        .frame_interval()
+        .map_or(Duration::from_millis(opts.poll_ms), |frame| {
+            frame.min(Duration::from_millis(opts.poll_ms))
+        });
 
     // Component loop.
     loop {
@@ -710,20 +4311,37 @@ fn main() -> Result<(), ()> {
             break;
         }
 
-        if !poll(Duration::from_millis(50)).unwrap() {
+        // show whatever the last update and tick drew
+        screen.flush().drop_error()?;
+
+        if timeout.is_some_and(|timeout| started.elapsed() > timeout) {
+            timed_out = true;
+            break;
+        }
+
+        if !poll(poll_interval).unwrap() {
             continue;
         }
 
-        let event = read().drop_error()?;
+        let mut event = read().drop_error()?;
+        if let Some(keymap) = &opts.keymap {
+            if let Some(action) = keymap.action(&event, component.typing()) {
+                event = component.key_for(action).event();
+            }
+        }
+
+        // exit on control c, or whichever key was chosen
+        if opts.cancel_key.matches(&event) {
+            interrupted = true;
+            break;
+        }
 
-        // exit on control c
+        // cancel on escape, distinguishable from errors by the exit code
         if let Event::Key(KeyEvent {
-            code: KeyCode::Char('c'),
-            modifiers: KeyModifiers::CONTROL,
-            ..
+            code: KeyCode::Esc, ..
         }) = event
         {
-            interrupted = true;
+            cancelled = true;
             break;
         }
         if component.update(&event, &mut screen)? {
@@ -731,16 +4349,197 @@ fn main() -> Result<(), ()> {
         }
     }
     disable_raw_mode().drop_error()?;
-    execute!(screen, Show, LeaveAlternateScreen).drop_error()?;
+    if mouse {
+        execute!(screen, DisableMouseCapture).drop_error()?;
+    }
+    if cursor_shaped {
+        execute!(screen, SetCursorStyle::DefaultUserShape).drop_error()?;
+    }
+    if opts.no_alt_screen {
+        let origin = screen.origin;
+        execute!(
+            screen,
+            MoveTo(0, origin),
+            Clear(ClearType::FromCursorDown),
+            Show
+        )
+        .drop_error()?;
+    } else {
+        execute!(screen, Show, LeaveAlternateScreen).drop_error()?;
+    }
+
+    Ok((interrupted, cancelled, timed_out))
+}
+
+fn main() -> Result<(), ()> {
+    let opts = Opts::from_args();
+
+    // Components draw to stderr, so it has to be a terminal
+    if !opts.non_interactive && !stderr().is_terminal() {
+        eprintln!("rum: stderr is not a terminal");
+        std::process::exit(1);
+    }
+
+    // Create component
+    let mut component = match Component::from_opts(&opts) {
+        Ok(component) => component,
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }
+    };
+
+    let (interrupted, cancelled, timed_out) = if opts.non_interactive {
+        component.settle();
+        (false, false, false)
+    } else {
+        interact(&mut component, &opts)?
+    };
 
+    let finished = !(interrupted || cancelled || timed_out);
+    // choose is output as a list in JSON
+    let selected = match &component {
+        Component::Choose {
+            ordered,
+            print_index,
+            state,
+            ..
+        } => Some(if finished {
+            state.selected(*ordered, *print_index)
+        } else {
+            vec![]
+        }),
+        _ => None,
+    };
+    // confirm answers with the exit code, but JSON has room for the label
+    let answer = match &component {
+        Component::Confirm { labels, state, .. } if finished => {
+            Some(labels[state.selected].clone())
+        }
+        _ => None,
+    };
+    // the spinner's own --timeout counts too
+    let spinner_timed_out =
+        matches!(&component, Component::Spinner { state, .. } if state.timed_out);
+
+    if !finished {
+        component.abort();
+    }
     let (to_print, err_code) = if interrupted {
         ("".to_owned(), 1)
+    } else if cancelled {
+        ("".to_owned(), 130)
+    } else if timed_out {
+        ("".to_owned(), 124)
     } else {
         component.result()?
     };
 
-    print!("{}", to_print);
+    let to_print = if opts.json {
+        let value = match selected {
+            Some(selected) => {
+                let items: Vec<String> = selected.iter().map(|item| json_string(item)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            None => json_string(answer.as_ref().unwrap_or(&to_print)),
+        };
+        format!(
+            "{{\"value\": {value}, \"cancelled\": {}, \"timed_out\": {}}}\n",
+            interrupted || cancelled,
+            timed_out || spinner_timed_out
+        )
+    } else {
+        to_print
+    };
+
+    match &opts.output {
+        Some(path) => {
+            if let Err(e) = fs::write(path, to_print) {
+                eprintln!("{}: {e}", path.display());
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", to_print),
+    }
 
     // std::process::exit is a divergent function
     std::process::exit(err_code as i32);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn component(args: &[&str]) -> Component {
+        let args = std::iter::once("rum").chain(args.iter().copied());
+        Component::from_opts(&Opts::from_iter(args)).unwrap()
+    }
+
+    #[test]
+    fn wrap_measures_display_width() {
+        assert_eq!(
+            wrap("hello wonderful world", 10),
+            ["hello", "wonderful", "world"]
+        );
+        assert_eq!(
+            wrap("日本語のテキスト", 6),
+            ["日本語", "の", "テキス", "ト"]
+        );
+    }
+
+    #[test]
+    fn parse_key_reads_modifiers_and_names() {
+        let key = parse_key("ctrl-alt-x").unwrap();
+        assert_eq!(key.code, KeyCode::Char('x'));
+        assert_eq!(key.modifiers, KeyModifiers::CONTROL | KeyModifiers::ALT);
+        assert_eq!(parse_key("PageDown").unwrap().code, KeyCode::PageDown);
+        assert_eq!(parse_key("ctrl--").unwrap().code, KeyCode::Char('-'));
+        assert!(parse_key("hyper-x").is_err());
+        assert!(parse_key("nope").is_err());
+    }
+
+    #[test]
+    fn parse_keymap_reads_bindings() {
+        let keymap = parse_keymap("down=j, submit=ctrl-d,").unwrap();
+        assert_eq!(keymap.0.len(), 2);
+        assert_eq!(keymap.0[0].1, Action::Down);
+        assert_eq!(keymap.0[1].1, Action::Submit);
+        assert!(parse_keymap("fly=j").is_err());
+        assert!(parse_keymap("down").is_err());
+        assert!(parse_keymap("down=zz").is_err());
+    }
+
+    #[test]
+    fn plain_keys_are_typed_instead_of_bound() {
+        let keymap = parse_keymap("down=j,submit=ctrl-x").unwrap();
+        let j = press(KeyCode::Char('j'), KeyModifiers::NONE);
+        let ctrl_x = press(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.action(&j, false), Some(Action::Down));
+        assert_eq!(keymap.action(&j, true), None);
+        assert_eq!(keymap.action(&ctrl_x, true), Some(Action::Submit));
+    }
+
+    #[test]
+    fn components_resolve_submit_to_their_own_key() {
+        let text = component(&["text", "--submit-on", "ctrl-d"]);
+        assert!(text.typing());
+        let key = text.key_for(Action::Submit);
+        assert_eq!(key.code, KeyCode::Char('d'));
+        assert_eq!(key.modifiers, KeyModifiers::CONTROL);
+
+        let textarea = component(&["textarea"]);
+        assert_eq!(
+            textarea.key_for(Action::Submit).modifiers,
+            KeyModifiers::CONTROL
+        );
+
+        let confirm = component(&["confirm"]);
+        assert!(!confirm.typing());
+        assert_eq!(confirm.key_for(Action::Submit).code, KeyCode::Enter);
+        assert_eq!(confirm.key_for(Action::Toggle).code, KeyCode::Char(' '));
+    }
+}
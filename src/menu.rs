@@ -0,0 +1,71 @@
+#[derive(Debug)]
+pub struct MenuItem {
+    pub label: String,
+    /// Byte index of the accelerator within `label`
+    pub key: Option<usize>,
+}
+
+impl MenuItem {
+    /// Parse a line like "&Quit", where the character after '&' is the accelerator.
+    /// A doubled "&&" is a literal '&'.
+    pub fn parse(line: &str) -> Self {
+        let mut label = String::new();
+        let mut key = None;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c != '&' {
+                label.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('&') => label.push('&'),
+                Some(c) => {
+                    key.get_or_insert(label.len());
+                    label.push(c);
+                }
+                None => label.push('&'),
+            }
+        }
+        MenuItem { label, key }
+    }
+
+    /// The accelerator character, if any
+    pub fn accelerator(&self) -> Option<char> {
+        self.key.and_then(|i| self.label[i..].chars().next())
+    }
+}
+
+#[derive(Debug)]
+pub struct MenuState {
+    pub items: Vec<MenuItem>,
+    pub cursor_loc: usize,
+    /// First visible row
+    pub offset: usize,
+}
+
+impl MenuState {
+    pub fn new(lines: Vec<String>) -> Self {
+        MenuState {
+            items: lines.iter().map(|line| MenuItem::parse(line)).collect(),
+            cursor_loc: 0,
+            offset: 0,
+        }
+    }
+
+    /// Index of the first item whose accelerator is `c`, ignoring case
+    pub fn find_accelerator(&self, c: char) -> Option<usize> {
+        self.items.iter().position(|item| {
+            item.accelerator()
+                .is_some_and(|key| key.to_lowercase().eq(c.to_lowercase()))
+        })
+    }
+
+    /// Keep the cursor within a viewport of `height` rows
+    pub fn scroll(&mut self, height: usize) {
+        if self.cursor_loc < self.offset {
+            self.offset = self.cursor_loc;
+        } else if self.cursor_loc >= self.offset + height {
+            self.offset = self.cursor_loc + 1 - height;
+        }
+    }
+}
@@ -0,0 +1,46 @@
+#[derive(Debug)]
+pub struct PagerState {
+    pub lines: Vec<String>,
+    /// First visible line
+    pub offset: usize,
+    /// Whether the last key was a 'g', for "gg"
+    pub pending_g: bool,
+}
+
+impl PagerState {
+    pub fn new(lines: Vec<String>) -> Self {
+        PagerState {
+            lines,
+            offset: 0,
+            pending_g: false,
+        }
+    }
+
+    fn max_offset(&self, height: usize) -> usize {
+        self.lines.len().saturating_sub(height)
+    }
+
+    /// Scroll by `delta` lines, returning whether the offset changed
+    pub fn scroll_by(&mut self, delta: isize, height: usize) -> bool {
+        let offset = self
+            .offset
+            .saturating_add_signed(delta)
+            .min(self.max_offset(height));
+        let changed = offset != self.offset;
+        self.offset = offset;
+        changed
+    }
+
+    pub fn top(&mut self) -> bool {
+        let changed = self.offset != 0;
+        self.offset = 0;
+        changed
+    }
+
+    pub fn bottom(&mut self, height: usize) -> bool {
+        let offset = self.max_offset(height);
+        let changed = offset != self.offset;
+        self.offset = offset;
+        changed
+    }
+}
@@ -0,0 +1,15 @@
+use crate::wrap;
+
+#[derive(Debug)]
+pub struct PauseState {
+    /// The message, wrapped once up front
+    pub lines: Vec<String>,
+}
+
+impl PauseState {
+    pub fn new(text: &str, width: usize) -> Self {
+        PauseState {
+            lines: wrap(text, width),
+        }
+    }
+}
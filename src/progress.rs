@@ -0,0 +1,45 @@
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+#[derive(Debug)]
+pub struct ProgressState {
+    /// Percentage between 0 and 100
+    pub percent: f64,
+    input: Receiver<String>,
+}
+
+impl ProgressState {
+    pub fn new(input: Receiver<String>) -> Self {
+        ProgressState {
+            percent: 0.0,
+            input,
+        }
+    }
+
+    /// Apply any new percentages, returning whether the progress changed
+    /// and whether it is finished
+    pub fn poll(&mut self) -> (bool, bool) {
+        let mut changed = false;
+        loop {
+            match self.input.try_recv() {
+                Ok(line) => {
+                    // ignore lines that are not numbers
+                    if let Ok(percent) = line.trim().parse::<f64>() {
+                        self.percent = percent.clamp(0.0, 100.0);
+                        changed = true;
+                    }
+                    if self.percent >= 100.0 {
+                        return (changed, true);
+                    }
+                }
+                Err(TryRecvError::Empty) => return (changed, false),
+                Err(TryRecvError::Disconnected) => return (changed, true),
+            }
+        }
+    }
+
+    /// Render the bar with `width` cells
+    pub fn bar(&self, width: usize) -> String {
+        let filled = (self.percent / 100.0 * width as f64).round() as usize;
+        format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+    }
+}
@@ -0,0 +1,97 @@
+#[derive(Debug)]
+pub struct SliderState {
+    min: f64,
+    max: f64,
+    step: f64,
+    /// Number of steps between the minimum and maximum, the last one
+    /// possibly short so that the maximum can be reached
+    steps: usize,
+    /// Steps above the minimum
+    pub index: usize,
+    /// Decimal places shown, enough for the minimum and step
+    precision: usize,
+}
+
+impl SliderState {
+    pub fn new(min: f64, max: f64, step: f64, value: f64) -> Result<Self, String> {
+        if max <= min {
+            return Err("The maximum must be greater than the minimum".to_owned());
+        }
+        if step <= 0.0 {
+            return Err("The step must be positive".to_owned());
+        }
+        if !(min..=max).contains(&value) {
+            return Err(format!("The value must be between {min} and {max}"));
+        }
+
+        // allow for rounding, as in 0.3 / 0.1 = 2.9999999999999996
+        let steps = ((max - min) / step - TOLERANCE).ceil() as usize;
+        Ok(SliderState {
+            min,
+            max,
+            step,
+            steps,
+            index: (((value - min) / step).round() as usize).min(steps),
+            precision: decimals(min).max(decimals(step)),
+        })
+    }
+
+    /// Move by `delta` steps, returning whether the value changed
+    pub fn step_by(&mut self, delta: isize) -> bool {
+        let index = self.index.saturating_add_signed(delta).min(self.steps);
+        let changed = index != self.index;
+        self.index = index;
+        changed
+    }
+
+    pub fn value(&self) -> String {
+        let value = (self.min + self.index as f64 * self.step).min(self.max);
+        format!("{value:.*}", self.precision)
+    }
+
+    /// Render the track with `width` cells
+    pub fn bar(&self, width: usize) -> String {
+        let width = width.max(1);
+        let handle = self.index * (width - 1) / self.steps.max(1);
+        format!("{}●{}", "━".repeat(handle), "─".repeat(width - 1 - handle))
+    }
+}
+
+const TOLERANCE: f64 = 1e-9;
+
+/// Number of decimal places in the shortest representation of `x`
+fn decimals(x: f64) -> usize {
+    x.to_string().split_once('.').map_or(0, |(_, d)| d.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_is_the_last_stop() {
+        let mut slider = SliderState::new(0.0, 10.0, 4.0, 0.0).unwrap();
+        slider.step_by(isize::MAX);
+        assert_eq!(slider.value(), "10");
+        slider.step_by(-1);
+        assert_eq!(slider.value(), "8");
+    }
+
+    #[test]
+    fn value_rounds_to_a_step_within_range() {
+        let slider = SliderState::new(0.0, 10.0, 4.0, 10.0).unwrap();
+        assert_eq!(slider.value(), "10");
+        assert_eq!(slider.bar(5), "━━━━●");
+    }
+
+    #[test]
+    fn fractional_steps_reach_max() {
+        let mut slider = SliderState::new(0.0, 0.3, 0.1, 0.0).unwrap();
+        slider.step_by(isize::MAX);
+        assert_eq!(slider.value(), "0.3");
+
+        let slider = SliderState::new(0.0, 0.3, 0.1, 0.3).unwrap();
+        assert_eq!(slider.value(), "0.3");
+        assert_eq!(slider.bar(4), "━━━●");
+    }
+}
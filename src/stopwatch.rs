@@ -0,0 +1,32 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct StopwatchState {
+    started: Instant,
+    /// Whole seconds elapsed, as last drawn
+    pub elapsed: u64,
+    /// Time at which the stopwatch was stopped
+    pub stopped: Option<Duration>,
+}
+
+impl StopwatchState {
+    pub fn new() -> Self {
+        StopwatchState {
+            started: Instant::now(),
+            elapsed: 0,
+            stopped: None,
+        }
+    }
+
+    /// Update the seconds elapsed, returning whether they changed
+    pub fn poll(&mut self) -> bool {
+        let elapsed = self.started.elapsed().as_secs();
+        let changed = elapsed != self.elapsed;
+        self.elapsed = elapsed;
+        changed
+    }
+
+    pub fn stop(&mut self) {
+        self.stopped = Some(self.started.elapsed());
+    }
+}
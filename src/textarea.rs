@@ -0,0 +1,140 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::grapheme_byte_index;
+
+#[derive(Debug)]
+pub struct TextareaState {
+    pub lines: Vec<String>,
+    /// Line of the cursor
+    pub row: usize,
+    /// Grapheme index of the cursor within its line
+    pub col: usize,
+    /// First visible wrapped row
+    pub offset: usize,
+}
+
+impl Default for TextareaState {
+    fn default() -> Self {
+        TextareaState {
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+            offset: 0,
+        }
+    }
+}
+
+impl TextareaState {
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].graphemes(true).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.len() == 1 && self.lines[0].is_empty()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let line = &mut self.lines[self.row];
+        let i = grapheme_byte_index(line, self.col);
+        line.insert(i, c);
+        self.col = (self.col + 1).min(self.line_len(self.row));
+    }
+
+    /// Split the current line at the cursor
+    pub fn newline(&mut self) {
+        let line = &mut self.lines[self.row];
+        let rest = line.split_off(grapheme_byte_index(line, self.col));
+        self.row += 1;
+        self.col = 0;
+        self.lines.insert(self.row, rest);
+    }
+
+    /// Delete the grapheme before the cursor, joining lines at the start of a line.
+    /// Returns whether anything was deleted
+    pub fn backspace(&mut self) -> bool {
+        if self.col > 0 {
+            let line = &mut self.lines[self.row];
+            let start = grapheme_byte_index(line, self.col - 1);
+            let end = grapheme_byte_index(line, self.col);
+            line.replace_range(start..end, "");
+            self.col -= 1;
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    // The cursor movements return whether the cursor moved
+
+    pub fn left(&mut self) -> bool {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    pub fn right(&mut self) -> bool {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    pub fn up(&mut self) -> bool {
+        if self.row == 0 {
+            return false;
+        }
+        self.row -= 1;
+        self.col = self.col.min(self.line_len(self.row));
+        true
+    }
+
+    pub fn down(&mut self) -> bool {
+        if self.row + 1 == self.lines.len() {
+            return false;
+        }
+        self.row += 1;
+        self.col = self.col.min(self.line_len(self.row));
+        true
+    }
+
+    /// Lines wrapped at `width` graphemes, as (line, first grapheme) pairs
+    pub fn wrapped(&self, width: usize) -> Vec<(usize, usize)> {
+        let width = width.max(1);
+        let mut rows = vec![];
+        for row in 0..self.lines.len() {
+            for k in 0..=self.line_len(row) / width {
+                rows.push((row, k * width));
+            }
+        }
+        rows
+    }
+
+    /// Wrapped row and column of the cursor
+    pub fn cursor_position(&self, width: usize) -> (usize, usize) {
+        let width = width.max(1);
+        let before: usize = (0..self.row)
+            .map(|row| self.line_len(row) / width + 1)
+            .sum();
+        (before + self.col / width, self.col % width)
+    }
+
+    pub fn result(self) -> String {
+        self.lines.join("\n")
+    }
+}
@@ -0,0 +1,32 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct TimerState {
+    duration: Duration,
+    started: Instant,
+    /// Whole seconds left, rounded up
+    pub remaining: u64,
+    /// Whether a key ended the timer early
+    pub skipped: bool,
+}
+
+impl TimerState {
+    pub fn new(duration: Duration) -> Self {
+        TimerState {
+            duration,
+            started: Instant::now(),
+            remaining: duration.as_secs() + (duration.subsec_nanos() > 0) as u64,
+            skipped: false,
+        }
+    }
+
+    /// Update the seconds left, returning whether they changed
+    /// and whether the timer is finished
+    pub fn poll(&mut self) -> (bool, bool) {
+        let left = self.duration.saturating_sub(self.started.elapsed());
+        let remaining = left.as_secs() + (left.subsec_nanos() > 0) as u64;
+        let changed = remaining != self.remaining;
+        self.remaining = remaining;
+        (changed, left.is_zero())
+    }
+}